@@ -0,0 +1,83 @@
+use crate::{
+    config::Config,
+    consts,
+    sources::meta::DATE_FORMAT,
+    util::{date, parser, slug::title_from_slug},
+};
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use serde_yaml::{Mapping, Value};
+use std::{fs, path::Path};
+
+/// Creates a new content entry in a collection, `entry_path` is the path of the entry without
+/// extension relative to the source directory
+///
+/// The front matter and content are copied from the archetype of the collection if it has one.
+pub fn create_entry(source_dir: &Path, config: &Config, entry_path: &Path) -> Result<()> {
+    let collection_dir = source_dir.join(entry_path.parent().unwrap_or(Path::new("")));
+    if !collection_dir.join(consts::COLLECTION_CONFIG_FN).exists() {
+        return Err(anyhow!(
+            "The directory '{}' is not a collection, it has no {} file!",
+            collection_dir.display(),
+            consts::COLLECTION_CONFIG_FN
+        ));
+    }
+    let slug = entry_path
+        .file_name()
+        .context("Missing the name of the entry")?
+        .to_string_lossy()
+        .to_string();
+    let target_path = collection_dir
+        .join(&slug)
+        .with_extension(&config.content_ext);
+    if target_path.exists() {
+        return Err(anyhow!(
+            "The file '{}' already exists!",
+            target_path.display()
+        ));
+    }
+
+    let archetype_path = collection_dir
+        .join(consts::ARCHETYPE_SOURCE_FS)
+        .with_extension(&config.content_ext);
+    let (archetype_meta, content) = {
+        if archetype_path.exists() {
+            let archetype_str = fs::read_to_string(&archetype_path)?;
+            let (front_matter, content) =
+                parser::split_front_matter(&archetype_str).with_context(|| {
+                    format!("Failed to read archetype '{}'", archetype_path.display())
+                })?;
+            let meta = match front_matter {
+                Some(front_matter) => front_matter.to_mapping().with_context(|| {
+                    format!("Failed to parse archetype '{}'", archetype_path.display())
+                })?,
+                None => Mapping::new(),
+            };
+            (meta, content.to_string())
+        } else {
+            (Mapping::new(), String::new())
+        }
+    };
+
+    let mut meta = Mapping::new();
+    meta.insert(
+        Value::String("title".to_string()),
+        Value::String(title_from_slug(&slug)),
+    );
+    meta.insert(
+        Value::String("date".to_string()),
+        Value::String(date::today(config.timezone).format(DATE_FORMAT).to_string()),
+    );
+    for (key, value) in archetype_meta {
+        if !meta.contains_key(&key) {
+            meta.insert(key, value);
+        }
+    }
+    let meta_str = serde_yaml::to_string(&meta)?;
+    let meta_str = meta_str.trim_start_matches("---\n");
+
+    fs::write(&target_path, format!("---\n{meta_str}---\n{content}"))
+        .with_context(|| format!("Failed to write '{}'", target_path.display()))?;
+    info!("Created '{}'", target_path.display());
+    Ok(())
+}
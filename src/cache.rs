@@ -0,0 +1,312 @@
+use crate::{consts::CONFIG_FN, util::git::GitDates};
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset};
+use log::{info, trace, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+/// Hashes of the inputs and dependencies of the outputs of a build
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct BuildCache {
+    version: String,
+    options: String,
+    /// Hashes of input files, relative to the source directory
+    hashes: HashMap<PathBuf, String>,
+    /// Inputs of every generated output, relative to the output directory
+    outputs: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+/// Keeps track of the outputs of a build to only regenerate outputs with changed inputs
+pub struct BuildState {
+    source_dir: PathBuf,
+    out_dir: PathBuf,
+    cache_path: Option<PathBuf>,
+    full_build: bool,
+    previous: BuildCache,
+    current: BuildCache,
+    /// Dates of the last commits of the inputs, which end up in the output as `updated` dates
+    git_dates: GitDates,
+}
+
+impl BuildState {
+    /// Loads the cache of the previous build into the same output directory
+    ///
+    /// Everything is regenerated if there is no usable cache, the build options or configuration
+    /// changed or when `clean` is set. The `git_dates` are hashed with the contents of the inputs.
+    pub fn load(
+        source_dir: &Path,
+        out_dir: &Path,
+        options: u64,
+        clean: bool,
+        git_dates: GitDates,
+    ) -> Self {
+        let cache_path = cache_path(out_dir);
+        Self::load_from(cache_path, source_dir, out_dir, options, clean, git_dates)
+    }
+
+    /// Loads the cache of the previous build from `cache_path`, which is not saved when unset
+    fn load_from(
+        cache_path: Option<PathBuf>,
+        source_dir: &Path,
+        out_dir: &Path,
+        options: u64,
+        clean: bool,
+        git_dates: GitDates,
+    ) -> Self {
+        let mut current = BuildCache {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            options: format!("{options:016x}"),
+            ..Default::default()
+        };
+        let config_path = PathBuf::from(CONFIG_FN);
+        if let Some(hash) = hash_file(&source_dir.join(&config_path), None) {
+            current.hashes.insert(config_path.clone(), hash);
+        }
+
+        let previous = cache_path
+            .as_ref()
+            .filter(|_| !clean && out_dir.exists())
+            .and_then(|path| load_cache(path))
+            .filter(|previous| {
+                previous.version == current.version
+                    && previous.options == current.options
+                    && previous.hashes.get(&config_path) == current.hashes.get(&config_path)
+            });
+        let full_build = previous.is_none();
+        if full_build {
+            info!("Regenerating all files");
+        } else {
+            info!("Only regenerating changed files");
+        }
+
+        Self {
+            source_dir: source_dir.to_path_buf(),
+            out_dir: out_dir.to_path_buf(),
+            cache_path,
+            full_build,
+            previous: previous.unwrap_or_default(),
+            current,
+            git_dates,
+        }
+    }
+
+    /// Dates of the last commits of the source files
+    pub fn git_dates(&self) -> &GitDates {
+        &self.git_dates
+    }
+
+    /// Whether the previous output can't be reused and has to be removed
+    pub fn is_full_build(&self) -> bool {
+        self.full_build
+    }
+
+    /// Whether the output was already generated during this build
+    pub fn is_generated(&self, output: &Path) -> bool {
+        output
+            .strip_prefix(&self.out_dir)
+            .is_ok_and(|output| self.current.outputs.contains_key(output))
+    }
+
+    /// Registers an output with the source files it depends on and returns whether it has to be
+    /// (re)generated, which is the case when it doesn't exist or any of its inputs changed
+    pub fn is_outdated(&mut self, output: &Path, mut inputs: Vec<PathBuf>) -> Result<bool> {
+        let output = output.strip_prefix(&self.out_dir)?.to_path_buf();
+        inputs.sort();
+        inputs.dedup();
+        for input in inputs.iter() {
+            if !self.current.hashes.contains_key(input) {
+                let path = self.source_dir.join(input);
+                if let Some(hash) = hash_file(&path, self.git_dates.get(&path)) {
+                    self.current.hashes.insert(input.clone(), hash);
+                }
+            }
+        }
+
+        let is_outdated = !self.out_dir.join(&output).exists()
+            || self.previous.outputs.get(&output) != Some(&inputs)
+            || inputs.iter().any(|input| {
+                let hash = self.current.hashes.get(input);
+                hash.is_none() || hash != self.previous.hashes.get(input)
+            });
+        self.current.outputs.insert(output, inputs);
+        Ok(is_outdated)
+    }
+
+    /// Removes outputs of the previous build that were not generated anymore and saves the cache
+    pub fn finish(self) -> Result<()> {
+        let mut stale_outputs: Vec<&PathBuf> = self
+            .previous
+            .outputs
+            .keys()
+            .filter(|output| !self.current.outputs.contains_key(*output))
+            .collect();
+        // Remove the contents of a directory before the directory itself
+        stale_outputs.sort();
+        for output in stale_outputs.into_iter().rev() {
+            let path = self.out_dir.join(output);
+            trace!("Remove stale output '{}'", output.display());
+            if path.is_dir() {
+                // Directories can also contain files that aren't generated
+                fs::remove_dir(&path).ok();
+            } else if path.exists() {
+                fs::remove_file(&path).with_context(|| {
+                    format!("Failed to remove stale output '{}'", path.display())
+                })?;
+            }
+        }
+
+        if let Some(cache_path) = &self.cache_path {
+            if let Err(e) = save_cache(cache_path, &self.current) {
+                warn!("Failed to save the build cache: {e:#}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Location of the cache for an output directory, outside of it to keep the output clean
+fn cache_path(out_dir: &Path) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    out_dir.hash(&mut hasher);
+    Some(
+        dirs::cache_dir()?
+            .join("renatic")
+            .join(format!("{:x}.yaml", hasher.finish())),
+    )
+}
+
+fn load_cache(path: &Path) -> Option<BuildCache> {
+    let cache_str = fs::read_to_string(path).ok()?;
+    serde_yaml::from_str(&cache_str).ok()
+}
+
+fn save_cache(path: &Path, cache: &BuildCache) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_yaml::to_string(cache)?)?;
+    Ok(())
+}
+
+fn hash_file(path: &Path, git_date: Option<DateTime<FixedOffset>>) -> Option<String> {
+    let contents = fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    git_date.hash(&mut hasher);
+    Some(format!("{:016x}", hasher.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a new temporary directory with the source and output directories of a site
+    fn site(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("renatic-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("source")).unwrap();
+        fs::create_dir_all(dir.join("out")).unwrap();
+        dir
+    }
+
+    fn load(dir: &Path, options: u64, clean: bool) -> BuildState {
+        BuildState::load_from(
+            Some(dir.join("cache.yaml")),
+            &dir.join("source"),
+            &dir.join("out"),
+            options,
+            clean,
+            GitDates::default(),
+        )
+    }
+
+    /// Builds `page.html` from `page.md`, like a generator writing the outputs that are outdated
+    fn build(dir: &Path, options: u64, clean: bool) -> bool {
+        let mut state = load(dir, options, clean);
+        let output = dir.join("out/page.html");
+        let is_outdated = state
+            .is_outdated(&output, vec![PathBuf::from("page.md")])
+            .unwrap();
+        if is_outdated {
+            fs::write(&output, "").unwrap();
+        }
+        state.finish().unwrap();
+        is_outdated
+    }
+
+    #[test]
+    fn unchanged_inputs() {
+        let dir = site("unchanged");
+        fs::write(dir.join("source/page.md"), "Content").unwrap();
+        assert!(build(&dir, 0, false));
+        assert!(!build(&dir, 0, false));
+    }
+
+    #[test]
+    fn changed_inputs() {
+        let dir = site("changed");
+        fs::write(dir.join("source/page.md"), "Content").unwrap();
+        assert!(build(&dir, 0, false));
+        fs::write(dir.join("source/page.md"), "Changed").unwrap();
+        assert!(build(&dir, 0, false));
+
+        // A new input of an output makes it outdated as well
+        let mut state = load(&dir, 0, false);
+        let inputs = vec![PathBuf::from("page.md"), PathBuf::from("template.html")];
+        assert!(state
+            .is_outdated(&dir.join("out/page.html"), inputs)
+            .unwrap());
+    }
+
+    #[test]
+    fn full_builds() {
+        let dir = site("full");
+        fs::write(dir.join("source/page.md"), "Content").unwrap();
+        assert!(build(&dir, 0, false));
+        assert!(build(&dir, 0, true));
+        // Other build options
+        assert!(build(&dir, 1, false));
+        assert!(!load(&dir, 1, false).is_full_build());
+        // A changed configuration
+        fs::write(dir.join("source").join(CONFIG_FN), "sitemap: false").unwrap();
+        assert!(load(&dir, 1, false).is_full_build());
+        // A removed output
+        fs::remove_file(dir.join("out/page.html")).unwrap();
+        assert!(build(&dir, 1, false));
+    }
+
+    #[test]
+    fn finish_removes_stale_outputs() {
+        let dir = site("stale");
+        let mut state = load(&dir, 0, false);
+        for output in [
+            "out/old",
+            "out/old/page.html",
+            "out/kept",
+            "out/kept/page.html",
+        ] {
+            let output = dir.join(output);
+            assert!(!state.is_generated(&output));
+            state.is_outdated(&output, Vec::new()).unwrap();
+            assert!(state.is_generated(&output));
+        }
+        for dir in [dir.join("out/old"), dir.join("out/kept")] {
+            fs::create_dir(&dir).unwrap();
+            fs::write(dir.join("page.html"), "").unwrap();
+        }
+        state.finish().unwrap();
+
+        // Directories with files that were not generated stay
+        fs::write(dir.join("out/kept/custom.html"), "").unwrap();
+        load(&dir, 0, false).finish().unwrap();
+        assert!(!dir.join("out/old").exists());
+        assert!(!dir.join("out/kept/page.html").exists());
+        assert!(dir.join("out/kept/custom.html").exists());
+    }
+}
@@ -1,8 +1,10 @@
+use crate::{sources::meta::DATE_FORMAT, util::date::Locale};
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Config {
     pub base_url: String,
@@ -12,6 +14,25 @@ pub struct Config {
     pub template_ext: String,
     pub target_ext: String,
     pub content_ext: String,
+    /// Template of the content outside collections that doesn't set one in its meta
+    pub template: Option<PathBuf>,
+    /// Timezone of dates without offset, like `Europe/Amsterdam`
+    pub timezone: Tz,
+    /// Accepted formats of dates in the meta next to RFC 3339 and `%Y-%m-%d`, like `%d-%m-%Y`
+    pub date_formats: Vec<String>,
+    /// Language of the month and weekday names of the `date` template filter
+    pub locale: Locale,
+    /// Use the date of the last commit that changed a content file as its `updated` date when
+    /// the meta doesn't set it
+    pub git_dates: bool,
+    /// Generate a sitemap of all pages
+    pub sitemap: bool,
+    /// Author of the feeds of all collections
+    pub author: Option<String>,
+    /// Default templates of the tag pages of all collections
+    pub tags: TaxonomyConfig,
+    /// Default templates of the category pages of all collections
+    pub categories: TaxonomyConfig,
 }
 
 impl Default for Config {
@@ -23,6 +44,19 @@ impl Default for Config {
             template_ext: String::from("html"),
             target_ext: String::from("html"),
             content_ext: String::from("md"),
+            template: None,
+            timezone: Tz::UTC,
+            date_formats: vec![
+                String::from(DATE_FORMAT),
+                String::from("%Y-%m-%dT%H:%M:%S"),
+                String::from("%Y-%m-%d %H:%M:%S"),
+            ],
+            locale: Locale::default(),
+            git_dates: false,
+            sitemap: true,
+            author: None,
+            tags: TaxonomyConfig::default(),
+            categories: TaxonomyConfig::default(),
         }
     }
 }
@@ -38,7 +72,7 @@ impl Config {
     }
 }
 
-#[derive(Default, Deserialize, Clone)]
+#[derive(Default, Deserialize, Serialize, Clone)]
 #[serde(default)]
 pub struct CollectionConfig {
     pub title: String,
@@ -46,6 +80,24 @@ pub struct CollectionConfig {
     pub template: Option<PathBuf>,
     pub connections: Vec<PathBuf>,
     pub rss: Option<PathBuf>,
+    /// Path of the Atom feed, which can be generated next to or instead of the RSS feed
+    pub atom: Option<PathBuf>,
+    /// Path of the JSON feed
+    pub json_feed: Option<PathBuf>,
+    /// Author of the feeds, overriding the author of the main configuration
+    pub author: Option<String>,
+    /// iTunes podcast information, which turns the RSS feed into a podcast feed
+    pub podcast: Option<PodcastConfig>,
+    /// Maximum number of the latest entries in the feeds, all entries when unset
+    pub feed_limit: Option<usize>,
+    /// What the feed items contain of every entry
+    pub feed_content: FeedContent,
+    /// Number of entries on every page of the collection index, all on one page when unset
+    pub paginate_by: Option<usize>,
+    /// Templates of the tag pages, overriding the defaults of the main configuration
+    pub tags: TaxonomyConfig,
+    /// Templates of the category pages, overriding the defaults of the main configuration
+    pub categories: TaxonomyConfig,
 }
 
 impl CollectionConfig {
@@ -55,3 +107,52 @@ impl CollectionConfig {
         Ok(config)
     }
 }
+
+/// Contents of feed items
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedContent {
+    /// The complete content
+    #[default]
+    Full,
+    /// The content up to a `<!-- more -->` marker or the first paragraph
+    Excerpt,
+    /// The `description` in the front matter, or the excerpt without description
+    Description,
+}
+
+/// Channel information of a podcast feed
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PodcastConfig {
+    /// Cover art, a route in the site or a full URL
+    pub image: String,
+    /// Author of the podcast, the author of the feed when unset
+    pub author: Option<String>,
+    /// Apple Podcasts category, like `Technology`
+    pub category: String,
+    #[serde(default)]
+    pub explicit: bool,
+}
+
+/// Pages and feeds of a taxonomy, a page is only generated when its template is set
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct TaxonomyConfig {
+    /// Template of the page listing all terms
+    pub list_template: Option<PathBuf>,
+    /// Template of the page of every term listing its entries
+    pub term_template: Option<PathBuf>,
+    /// Generate an RSS feed for every term
+    pub rss: Option<bool>,
+}
+
+impl TaxonomyConfig {
+    /// Uses the options of `defaults` for the options that aren't set
+    pub fn or(&self, defaults: &TaxonomyConfig) -> TaxonomyConfig {
+        TaxonomyConfig {
+            list_template: self.list_template.clone().or(defaults.list_template.clone()),
+            term_template: self.term_template.clone().or(defaults.term_template.clone()),
+            rss: self.rss.or(defaults.rss),
+        }
+    }
+}
@@ -2,4 +2,9 @@ pub const CONFIG_FN: &str = "renatic.yaml";
 pub const COLLECTION_CONFIG_FN: &str = "collection.yaml";
 pub const MINIFY_EXTS: [&str; 3] = ["html", "htm", "css"];
 pub const INDEX_SOURCE_FS: &str = "_index";
-pub const INDEX_TARGET_FS: &str = "index";
\ No newline at end of file
+pub const INDEX_TARGET_FS: &str = "index";
+pub const ARCHETYPE_SOURCE_FS: &str = "_archetype";
+pub const PAGE_DIR: &str = "page";
+pub const TERM_RSS_FN: &str = "rss.xml";
+pub const SITEMAP_FN: &str = "sitemap.xml";
+pub const LIVE_RELOAD_ROUTE: &str = "/__renatic/livereload";
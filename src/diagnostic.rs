@@ -0,0 +1,178 @@
+use anyhow::{anyhow, bail, Result};
+use log::{error, info, warn};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Error at a position in a source file, shown like a compiler error with the source line
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub message: String,
+    pub path: PathBuf,
+    /// Line and column of the error starting at 1, if it is known
+    pub position: Option<(usize, usize)>,
+    /// The source line at the position
+    pub snippet: Option<String>,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic at `position` in `source`, the contents of the file at `path`
+    pub fn new(
+        message: impl Into<String>,
+        path: &Path,
+        source: &str,
+        position: Option<(usize, usize)>,
+    ) -> Self {
+        let snippet = position
+            .and_then(|(line, _)| source.lines().nth(line.checked_sub(1)?))
+            .map(|line| line.trim_end().to_string());
+        Self {
+            message: message.into(),
+            path: path.to_path_buf(),
+            position,
+            snippet,
+        }
+    }
+}
+
+/// Line and column starting at 1 of a byte offset in `source`
+pub fn offset_position(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Message of an error and its causes, without repeated causes and, when the diagnostic shows
+/// the position, without the positions that parsers add like `at line 2 column 4`
+pub fn error_message(error: &anyhow::Error, has_position: bool) -> String {
+    let mut messages: Vec<String> = Vec::new();
+    for cause in error.chain() {
+        let message = cause.to_string();
+        let message = match has_position {
+            true => strip_position(&message),
+            false => &message,
+        };
+        if messages.last().map(String::as_str) != Some(message) {
+            messages.push(message.to_string());
+        }
+    }
+    messages.join(": ")
+}
+
+fn strip_position(message: &str) -> &str {
+    if let Some(index) = message.rfind(" at line ") {
+        let position = &message[index + " at line ".len()..];
+        if let Some((line, column)) = position.split_once(" column ") {
+            if line.parse::<usize>().is_ok() && column.parse::<usize>().is_ok() {
+                return &message[..index];
+            }
+        }
+    }
+    message
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.message)?;
+        let (line, column) = match self.position {
+            Some(position) => position,
+            None => return write!(f, "  --> {}", self.path.display()),
+        };
+        let gutter = " ".repeat(line.to_string().len());
+        write!(f, "{gutter} --> {}:{line}:{column}", self.path.display())?;
+        if let Some(snippet) = &self.snippet {
+            // Keep tabs so the marker lines up with the snippet
+            let indent: String = snippet
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            write!(
+                f,
+                "\n{gutter}  |\n {line} | {snippet}\n{gutter}  | {indent}^"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for Diagnostic {}
+
+/// Counts the errors and warnings of a build
+///
+/// By default the first error stops the build. With `keep_going` errors are logged and the build
+/// continues with the other items, so every problem is shown at once. With `strict` warnings are
+/// treated as errors.
+#[derive(Debug, Default)]
+pub struct Report {
+    keep_going: bool,
+    strict: bool,
+    errors: AtomicUsize,
+    warnings: AtomicUsize,
+}
+
+impl Report {
+    pub fn new(keep_going: bool, strict: bool) -> Self {
+        Self {
+            keep_going,
+            strict,
+            ..Default::default()
+        }
+    }
+
+    /// Logs and counts the error when the build keeps going, otherwise returns it
+    pub fn error(&self, error: anyhow::Error) -> Result<()> {
+        if !self.keep_going {
+            return Err(error);
+        }
+        error!("{error:?}");
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Logs and counts the warning, or reports it as an error in strict mode
+    pub fn warn(&self, message: impl Into<String>) -> Result<()> {
+        let message = message.into();
+        if self.strict {
+            return self.error(anyhow!(message));
+        }
+        warn!("{message}");
+        self.warnings.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors.load(Ordering::Relaxed) > 0
+    }
+
+    /// Logs the summary of the build, which fails when there were errors, `build` names the kind
+    /// of build like `Generation`
+    pub fn finish(&self, build: &str) -> Result<()> {
+        let errors = self.errors.load(Ordering::Relaxed);
+        let warnings = self.warnings.load(Ordering::Relaxed);
+        match (errors, warnings) {
+            (0, 0) => info!("{build} successfully completed!"),
+            (0, _) => info!("{build} completed with {}", count(warnings, "warning")),
+            (_, 0) => bail!("{build} failed with {}", count(errors, "error")),
+            _ => bail!(
+                "{build} failed with {} and {}",
+                count(errors, "error"),
+                count(warnings, "warning")
+            ),
+        }
+        Ok(())
+    }
+}
+
+fn count(amount: usize, noun: &str) -> String {
+    match amount {
+        1 => format!("1 {noun}"),
+        _ => format!("{amount} {noun}s"),
+    }
+}
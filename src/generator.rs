@@ -1,242 +1,1042 @@
 use crate::{
-    config::{CollectionConfig, Config},
+    cache::BuildState,
+    config::{CollectionConfig, Config, FeedContent, TaxonomyConfig},
     consts,
-    index::{self, IndexType},
+    diagnostic::Report,
+    index::{self, IndexItem, IndexType},
     renderer::ContentRenderer,
-    sources::content::{CollectionBinding, Entry, Location},
+    sources::{
+        content::{CollectionBinding, Entry, Location},
+        meta::Meta,
+        taxonomy::{Taxonomy, TaxonomyKind, TaxonomyPage},
+    },
     util::{
+        atom::{self, AtomCategory, AtomContent, AtomEntry, AtomFeed, AtomLink},
+        git::GitDates,
+        json_feed::{self, JsonFeed, JsonFeedAuthor, JsonFeedItem},
+        mime,
         minifier::{self, MinificationLevel},
-        rss::{self, RssChannel, RssFeed, RssGuid, RssItem},
+        rss::{
+            self, ItunesCategory, ItunesImage, RssCategory, RssChannel, RssEnclosure, RssFeed,
+            RssGuid, RssItem,
+        },
+        sitemap::{self, SitemapIndex, SitemapRef, SitemapUrl, UrlSet},
     },
 };
 use anyhow::{Context, Result};
-use chrono::{NaiveDateTime, NaiveTime};
-use log::{info, trace, warn};
-use std::{ffi::OsStr, fs, path::PathBuf};
+use chrono::{DateTime, FixedOffset, SubsecRound, Utc};
+use log::{info, trace};
+use rayon::prelude::*;
+use std::{
+    collections::hash_map::DefaultHasher,
+    ffi::OsStr,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
 
-pub fn generate(
-    source_dir: &PathBuf,
-    out_dir: &PathBuf,
-    mfc_level: &MinificationLevel,
-) -> Result<()> {
-    // Load main configuration
+#[derive(Clone)]
+pub struct BuildOptions {
+    pub mfc_level: MinificationLevel,
+    /// Inject the live reload script into rendered pages, only used for previews
+    pub live_reload: bool,
+    /// Ignore the build cache and regenerate everything
+    pub clean: bool,
+    /// Include content marked as draft
+    pub drafts: bool,
+    /// Include content with a date in the future
+    pub future: bool,
+    /// Continue after errors and report all of them at the end
+    pub keep_going: bool,
+    /// Treat warnings as errors
+    pub strict: bool,
+    /// Only check the site by rendering everything in memory, nothing is written
+    pub check: bool,
+}
+
+impl BuildOptions {
+    /// Hash of the options that change the generated output
+    fn output_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.mfc_level.hash(&mut hasher);
+        self.live_reload.hash(&mut hasher);
+        self.drafts.hash(&mut hasher);
+        self.future.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether content is published now with these options
+    fn is_published(&self, meta: &Meta) -> bool {
+        meta.is_published(Utc::now().into(), self.drafts, self.future)
+    }
+}
+
+pub struct Generator {
+    source_dir: PathBuf,
+    out_dir: PathBuf,
+    options: BuildOptions,
+    config: Config,
+    renderer: ContentRenderer,
+}
+
+impl Generator {
+    pub fn load(source_dir: &Path, out_dir: &Path, options: BuildOptions) -> Result<Self> {
+        let config = load_config(source_dir)?;
+        let renderer = ContentRenderer::load(source_dir.to_path_buf(), &config, &options)?;
+        Ok(Self {
+            source_dir: source_dir.to_path_buf(),
+            out_dir: out_dir.to_path_buf(),
+            options,
+            config,
+            renderer,
+        })
+    }
+
+    pub fn source_dir(&self) -> &Path {
+        &self.source_dir
+    }
+
+    pub fn out_dir(&self) -> &Path {
+        &self.out_dir
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Reloads the configuration and templates affected by the changed source files
+    pub fn reload(&mut self, changed_paths: &[PathBuf]) -> Result<()> {
+        let config_path = self.source_dir.join(consts::CONFIG_FN);
+        if changed_paths.contains(&config_path) {
+            info!("Configuration changed, reloading");
+            self.config = load_config(&self.source_dir)?;
+            self.renderer =
+                ContentRenderer::load(self.source_dir.clone(), &self.config, &self.options)?;
+        } else if changed_paths.iter().any(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == self.config.template_ext.as_str())
+        }) {
+            info!("Templates changed, reloading");
+            self.renderer.reload()?;
+        }
+        Ok(())
+    }
+
+    pub fn generate(&self) -> Result<()> {
+        let Self {
+            source_dir,
+            out_dir,
+            options,
+            config,
+            renderer,
+        } = self;
+        generate(source_dir, out_dir, config, renderer, options)
+    }
+}
+
+fn load_config(source_dir: &Path) -> Result<Config> {
     let config_path = source_dir.join(consts::CONFIG_FN);
-    let config = Config::load(&config_path).with_context(|| {
+    Config::load(&config_path).with_context(|| {
         format!(
             "Failed to load configuration file from '{}'",
             config_path.display()
         )
-    })?;
+    })
+}
+
+/// Independent output of a build, all tasks are run in parallel after the outputs are planned
+enum Task {
+    Template {
+        template_path: PathBuf,
+        entry: Option<Box<Entry>>,
+        target_path: PathBuf,
+    },
+    Sitemap {
+        urls: Vec<SitemapUrl>,
+        target_path: PathBuf,
+    },
+    SitemapIndex {
+        sitemaps: Vec<SitemapRef>,
+        target_path: PathBuf,
+    },
+    Minify {
+        source_path: PathBuf,
+        target_path: PathBuf,
+    },
+    Copy {
+        source_path: PathBuf,
+        target_path: PathBuf,
+    },
+    Taxonomy {
+        template_path: PathBuf,
+        page: TaxonomyPage,
+        target_path: PathBuf,
+    },
+    Feed {
+        format: FeedFormat,
+        entries: Vec<Entry>,
+        feed_path: PathBuf,
+        feed_out: PathBuf,
+        collection_cfg: Box<CollectionConfig>,
+        /// Name of the term for feeds of a taxonomy term
+        term: Option<String>,
+    },
+}
+
+/// Page that is listed in the sitemap
+struct SitemapPage {
+    route: String,
+    lastmod: Option<DateTime<FixedOffset>>,
+}
 
-    let renderer = ContentRenderer::load(source_dir.clone(), &config, mfc_level.clone())?;
+#[derive(Clone, Copy)]
+enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
 
-    if out_dir.exists() {
-        fs::remove_dir_all(out_dir).with_context(|| "Failed to remove previous output")?;
+impl Task {
+    fn run(
+        &self,
+        source_dir: &Path,
+        config: &Config,
+        renderer: &ContentRenderer,
+        options: &BuildOptions,
+    ) -> Result<()> {
+        match self {
+            Task::Template {
+                template_path,
+                entry: Some(entry),
+                target_path,
+            } => write_output(
+                target_path,
+                render_template(template_path, entry, renderer)?,
+                options,
+            ),
+            Task::Template {
+                template_path,
+                entry: None,
+                target_path,
+            } => {
+                trace!(
+                    "Generate template without source '{}'",
+                    template_path.display()
+                );
+                let html = renderer.render(template_path, None)?;
+                write_output(target_path, html, options)
+            }
+            Task::Sitemap { urls, target_path } => {
+                trace!("Generate sitemap '{}'", target_path.display());
+                let sitemap_str = sitemap::to_str(UrlSet::new(urls.clone()))?;
+                write_output(target_path, sitemap_str, options)
+            }
+            Task::SitemapIndex {
+                sitemaps,
+                target_path,
+            } => {
+                trace!("Generate sitemap index '{}'", target_path.display());
+                let index_str = sitemap::index_to_str(SitemapIndex::new(sitemaps.clone()))?;
+                write_output(target_path, index_str, options)
+            }
+            Task::Minify {
+                source_path,
+                target_path,
+            } => {
+                trace!(
+                    "Minify & copy non-template file '{}'",
+                    source_path.display()
+                );
+                let contents = fs::read_to_string(source_path)
+                    .with_context(|| format!("Failed to read '{}'", source_path.display()))?;
+                write_output(
+                    target_path,
+                    minifier::minify_string(&contents, &options.mfc_level),
+                    options,
+                )
+            }
+            Task::Copy {
+                source_path,
+                target_path,
+            } => {
+                if !options.check {
+                    fs::copy(source_path, target_path)
+                        .with_context(|| format!("Failed to copy '{}'", source_path.display()))?;
+                }
+                Ok(())
+            }
+            Task::Taxonomy {
+                template_path,
+                page,
+                target_path,
+            } => {
+                trace!("Generate taxonomy page '{}'", target_path.display());
+                let html = renderer
+                    .render(template_path, Some(page))
+                    .with_context(|| {
+                        format!(
+                            "Failed to generate taxonomy page '{}' using template '{}'",
+                            target_path.display(),
+                            template_path.display(),
+                        )
+                    })?;
+                write_output(target_path, html, options)
+            }
+            Task::Feed {
+                format,
+                entries,
+                feed_path,
+                feed_out,
+                collection_cfg,
+                term,
+            } => {
+                let term = term.as_deref();
+                let feed_str = match format {
+                    FeedFormat::Rss => generate_rss_feed(
+                        entries,
+                        feed_path,
+                        source_dir,
+                        config,
+                        collection_cfg,
+                        term,
+                    )
+                    .with_context(|| {
+                        format!("Failed to generate RSS feed '{}'", feed_path.display())
+                    })?,
+                    FeedFormat::Atom => {
+                        generate_atom_feed(entries, feed_path, config, collection_cfg, term)
+                            .with_context(|| {
+                                format!("Failed to generate Atom feed '{}'", feed_path.display())
+                            })?
+                    }
+                    FeedFormat::Json => {
+                        generate_json_feed(entries, feed_path, config, collection_cfg, term)
+                            .with_context(|| {
+                                format!("Failed to generate JSON feed '{}'", feed_path.display())
+                            })?
+                    }
+                };
+                write_output(feed_out, feed_str, options)
+            }
+        }
     }
-    fs::create_dir_all(out_dir).with_context(|| "Failed to create output directory")?;
+}
 
-    let file_index = index::index(source_dir)?;
+fn generate(
+    source_dir: &PathBuf,
+    out_dir: &PathBuf,
+    config: &Config,
+    renderer: &ContentRenderer,
+    options: &BuildOptions,
+) -> Result<()> {
+    let report = Report::new(options.keep_going, options.strict);
+    let git_dates = if config.git_dates {
+        match GitDates::load(source_dir) {
+            Ok(git_dates) => git_dates,
+            Err(e) => {
+                report.warn(format!(
+                    "Failed to read the dates of the git history: {e:#}"
+                ))?;
+                GitDates::default()
+            }
+        }
+    } else {
+        GitDates::default()
+    };
+    // A check plans every output like a clean build, without touching the output directory
+    let state = BuildState::load(
+        source_dir,
+        out_dir,
+        options.output_hash(),
+        options.clean || options.check,
+        git_dates,
+    );
+    if !options.check {
+        if state.is_full_build() && out_dir.exists() {
+            fs::remove_dir_all(out_dir).with_context(|| "Failed to remove previous output")?;
+        }
+        fs::create_dir_all(out_dir).with_context(|| "Failed to create output directory")?;
+    }
 
-    // Loop over the index for the genration
+    let file_index = index::index(source_dir)?;
+    let mut planner = Planner {
+        source_dir,
+        out_dir,
+        config,
+        renderer,
+        options,
+        report: &report,
+        state,
+        tasks: Vec::new(),
+        sitemap: Vec::new(),
+    };
+    // Plan the outputs of every item of the index, an error of an item only stops the build
+    // when it does not keep going
     for index_item in file_index {
-        let child_path = index_item.path.strip_prefix(source_dir)?;
+        if let Err(e) = planner.plan_item(index_item) {
+            report.error(e)?;
+        }
+    }
+    if config.sitemap {
+        planner.plan_sitemap()?;
+    }
+    let Planner { state, tasks, .. } = planner;
+
+    // Generate all outputs in parallel, errors are reported in the order of the index
+    if options.check {
+        info!("Rendering {} files in memory", tasks.len());
+    } else {
+        info!("Generating {} files", tasks.len());
+    }
+    let results: Vec<Result<()>> = tasks
+        .par_iter()
+        .map(|task| task.run(source_dir, config, renderer, options))
+        .collect();
+    for result in results {
+        if let Err(e) = result {
+            report.error(e)?;
+        }
+    }
+
+    if options.check {
+        return report.finish("Check");
+    }
+    // Keep the previous cache after errors, so the failed outputs are generated again
+    if !report.has_errors() {
+        state.finish()?;
+    }
+    report.finish("Generation")
+}
+
+/// Collects the tasks of a build, skipping the outputs of which the inputs didn't change
+struct Planner<'a> {
+    source_dir: &'a PathBuf,
+    out_dir: &'a PathBuf,
+    config: &'a Config,
+    renderer: &'a ContentRenderer,
+    options: &'a BuildOptions,
+    report: &'a Report,
+    state: BuildState,
+    tasks: Vec<Task>,
+    sitemap: Vec<SitemapPage>,
+}
+
+/// Loaded collection that is shared by the pages and feeds listing its entries
+struct Collection {
+    /// Directory of the collection relative to the source directory
+    child_dir: PathBuf,
+    cfg: CollectionConfig,
+    /// Published entries, the newest first
+    entries: Vec<Entry>,
+    /// Inputs of the pages listing the entries: every entry and the collection configuration
+    inputs: Vec<PathBuf>,
+}
+
+impl Planner<'_> {
+    /// Plans the outputs of a directory, file or collection of the index
+    fn plan_item(&mut self, index_item: IndexItem) -> Result<()> {
+        // Ignore the output of earlier builds when the output directory is in the source
+        if index_item.path.starts_with(self.out_dir) {
+            return Ok(());
+        }
+        let child_path = index_item.path.strip_prefix(self.source_dir)?;
         // Ignore hidden files starting with a '.'
-        if config.ignore_hidden && child_path.starts_with(".") {
-            continue;
+        if self.config.ignore_hidden && child_path.starts_with(".") {
+            return Ok(());
         }
         // Ignore paths according to the configuration
         let mut is_ignored = false;
-        for ignore_path in config.ignore_paths.iter() {
+        for ignore_path in self.config.ignore_paths.iter() {
             if child_path.starts_with(ignore_path) {
                 is_ignored = true;
                 break;
             }
         }
         if is_ignored {
-            continue;
+            return Ok(());
         }
 
-        let out_path = out_dir.join(&child_path);
+        let out_path = self.out_dir.join(&child_path);
         // TODO: Make this work when extension or filename changes
-        if out_path.exists() {
-            warn!(
+        if self.state.is_generated(&out_path) {
+            return self.report.warn(format!(
                 "The file '{}' was skipped because it was already generated in an earlier stage! \
                 Make sure you don't have dupplicate files or configure to ignore them",
                 child_path.display()
-            );
-            continue;
+            ));
         }
 
         match index_item.index_type {
             IndexType::Directory => {
-                trace!("Create direcory '{}'", &out_path.display());
-                fs::create_dir(out_path)?;
+                if self.state.is_outdated(&out_path, Vec::new())? {
+                    create_output_dir(&out_path, self.options)?;
+                }
+                Ok(())
+            }
+            IndexType::File => self.plan_file(&index_item.path, child_path, out_path),
+            IndexType::Collection => self.plan_collection(&index_item.path, child_path, &out_path),
+        }
+    }
+
+    /// Plans the output of a file outside collections: content is rendered with its template,
+    /// templates are rendered without content and other files are minified or copied
+    fn plan_file(&mut self, path: &PathBuf, child_path: &Path, out_path: PathBuf) -> Result<()> {
+        let ext = path.extension().unwrap_or(OsStr::new("")).to_str().unwrap();
+        // Content file
+        if ext == &self.config.content_ext {
+            let content = self.load_entry(path, &self.config.target_ext, None, None)?;
+            if self.options.is_published(&content.meta) {
+                self.plan_inclusive_template(content, Vec::new(), self.config.template.as_ref())?;
+            } else {
+                trace!("Skip unpublished content '{}'", child_path.display());
+            }
+        }
+        // Template file without source
+        else if ext == &self.config.template_ext {
+            let template_path = child_path.to_path_buf();
+            self.sitemap.push(SitemapPage {
+                route: format!("/{}", child_path.display()),
+                lastmod: None,
+            });
+            let inputs = self.renderer.dependencies(&template_path);
+            if self.state.is_outdated(&out_path, inputs)? {
+                self.tasks.push(Task::Template {
+                    template_path,
+                    entry: None,
+                    target_path: out_path,
+                });
             }
-            IndexType::File => {
-                let ext = index_item
-                    .path
+        }
+        // Skip unchanged files
+        else if !self
+            .state
+            .is_outdated(&out_path, vec![child_path.to_path_buf()])?
+        {
+            trace!("Skip unchanged file '{}'", child_path.display());
+        }
+        // Minifiable file
+        else if consts::MINIFY_EXTS.contains(&ext) {
+            self.tasks.push(Task::Minify {
+                source_path: path.clone(),
+                target_path: out_path,
+            });
+        }
+        // None of the above. A 'normal' file
+        else {
+            self.tasks.push(Task::Copy {
+                source_path: path.clone(),
+                target_path: out_path,
+            });
+        }
+        Ok(())
+    }
+
+    /// Plans the pages of the entries of a collection, the pages listing them and its feeds
+    fn plan_collection(
+        &mut self,
+        collection_dir: &Path,
+        child_path: &Path,
+        out_path: &Path,
+    ) -> Result<()> {
+        // Collection configuration
+        let cfg_path = collection_dir.join(consts::COLLECTION_CONFIG_FN);
+        let cfg = CollectionConfig::load(&cfg_path).with_context(|| {
+            format!(
+                "Failed to load collection configuration from '{}'",
+                cfg_path.display()
+            )
+        })?;
+        let cfg_child_path = child_path.join(consts::COLLECTION_CONFIG_FN);
+
+        if self.state.is_outdated(out_path, Vec::new())? {
+            create_output_dir(out_path, self.options)?;
+        }
+
+        info!("Generating collection '{}'", child_path.display());
+
+        // 1. Load all content entries of the collection
+        let mut entries = self.load_entries(collection_dir)?;
+
+        // 2. Sort the collection ascending by date
+        // TODO: Make this a optional feature
+        entries.sort_by(|a, b| b.meta.date.cmp(&a.meta.date));
+
+        // Pages with the binding depend on every entry of the collection
+        let mut inputs: Vec<PathBuf> = entries
+            .iter()
+            .map(|entry| entry.location.source_child_path.clone())
+            .collect();
+        inputs.push(cfg_child_path.clone());
+        let collection = Collection {
+            child_dir: child_path.to_path_buf(),
+            cfg,
+            entries,
+            inputs,
+        };
+
+        // 3. Generate templates
+        if let Some(template_path) = &collection.cfg.template {
+            for entry in collection.entries.iter() {
+                self.plan_template(template_path, entry.clone(), vec![cfg_child_path.clone()])?;
+            }
+        }
+        self.plan_audio_files(&collection)?;
+
+        // 4. Generate index and other connections, which can list the terms of the
+        // entries as well
+        let taxonomies: Vec<(TaxonomyKind, Arc<Taxonomy>)> = TaxonomyKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let has_rss = taxonomy_config(kind, &collection.cfg, self.config)
+                    .rss
+                    .unwrap_or(false);
+                let child_dir = child_path.join(kind.name());
+                let taxonomy = Taxonomy::new(kind, &child_dir, &collection.entries, has_rss);
+                (kind, Arc::new(taxonomy))
+            })
+            .collect();
+        let binding = CollectionBinding::new(
+            collection.entries.clone(),
+            taxonomies
+                .iter()
+                .map(|(kind, taxonomy)| (kind.name().to_string(), taxonomy.clone()))
+                .collect(),
+            &collection.cfg,
+        );
+        self.plan_connections(collection_dir, &collection, &binding)?;
+
+        // 5. Generate taxonomy pages and feeds if enabled
+        for (kind, taxonomy) in taxonomies.iter() {
+            self.plan_taxonomy(*kind, taxonomy, &collection)?;
+        }
+
+        // 6. Generate feeds if enabled
+        self.plan_feeds(&collection)
+    }
+
+    /// Loads the published entries of a collection, entries that fail to load are reported
+    fn load_entries(&self, collection_dir: &Path) -> Result<Vec<Entry>> {
+        let mut entry_paths = Vec::new();
+        for entry in fs::read_dir(collection_dir)? {
+            let entry_path = entry?.path().to_owned();
+            if entry_path.is_file()
+                && entry_path
                     .extension()
-                    .unwrap_or(OsStr::new(""))
-                    .to_str()
-                    .unwrap();
-                // Content file
-                if ext == &config.content_ext {
-                    let content = Entry::load(
-                        Location::new(
-                            &index_item.path,
-                            source_dir,
-                            out_dir,
-                            &config.target_ext,
-                            None,
-                        )?,
-                        None,
-                    )?;
-                    generate_inclusive_template(&content, &renderer)?;
-                }
-                // Template file without source
-                else if ext == &config.template_ext {
-                    trace!(
-                        "Generate template without source '{}'",
-                        &child_path.display()
-                    );
-                    let html = renderer.render(&index_item.path, None)?;
-                    fs::write(out_path, &html)?;
-                }
-                // Minifiable file
-                else if consts::MINIFY_EXTS.contains(&ext) {
-                    trace!(
-                        "Minify & copy non-template file '{}'",
-                        index_item.path.display()
-                    );
-                    let contents = fs::read_to_string(index_item.path)?;
-                    fs::write(out_path, minifier::minify_string(&contents, mfc_level))?;
-                }
-                // None of the above. A 'normal' file
-                else {
-                    fs::copy(index_item.path, out_path)?;
-                }
+                    .context("")?
+                    .eq_ignore_ascii_case(&self.config.content_ext)
+                && entry_path.file_stem().context("")? != consts::INDEX_SOURCE_FS
+                && entry_path.file_stem().context("")? != consts::ARCHETYPE_SOURCE_FS
+            {
+                entry_paths.push(entry_path);
             }
-            IndexType::Collection => {
-                // Collection configuration
-                let collection_cfg_path = index_item.path.join(consts::COLLECTION_CONFIG_FN);
-                let collection_cfg =
-                    CollectionConfig::load(&collection_cfg_path).with_context(|| {
-                        format!(
-                            "Failed to load collection configuration from '{}'",
-                            collection_cfg_path.display()
-                        )
-                    })?;
-                let collection_dir = &index_item.path;
-
-                fs::create_dir(&out_path)?;
-
-                info!("Generating collection '{}'", child_path.display());
-
-                // 1. Load all content entries of the collection
-                let mut entries = Vec::<Entry>::new();
-                for entry in fs::read_dir(collection_dir)? {
-                    let entry_path = entry?.path().to_owned();
-                    if entry_path.is_file()
-                        && entry_path
-                            .extension()
-                            .context("")?
-                            .eq_ignore_ascii_case(&config.content_ext)
-                        && entry_path.file_stem().context("")? != consts::INDEX_SOURCE_FS
-                    {
-                        let content = Entry::load(
-                            Location::new(
-                                &entry_path,
-                                &source_dir,
-                                &out_dir,
-                                &config.target_ext,
-                                None,
-                            )?,
-                            None,
-                        )
-                        .with_context(|| {
-                            format!("Failed to load content item '{}'", entry_path.display())
-                        })?;
-                        entries.push(content);
-                    }
-                }
+        }
+        // Keep the order of entries with the same date the same for every build
+        entry_paths.sort();
+        let results: Vec<Result<Entry>> = entry_paths
+            .par_iter()
+            .map(|entry_path| {
+                self.load_entry(entry_path, &self.config.target_ext, None, None)
+                    .with_context(|| {
+                        format!("Failed to load content item '{}'", entry_path.display())
+                    })
+            })
+            .collect();
+        // Leave out the entries that fail to load and report them
+        let mut entries = Vec::new();
+        for result in results {
+            match result {
+                Ok(entry) => entries.push(entry),
+                Err(e) => self.report.error(e)?,
+            }
+        }
+        entries.retain(|entry| {
+            let is_published = self.options.is_published(&entry.meta);
+            if !is_published {
+                trace!(
+                    "Skip unpublished content '{}'",
+                    entry.location.source_child_path.display()
+                );
+            }
+            is_published
+        });
+        for entry in entries.iter().filter(|entry| entry.meta.date.is_none()) {
+            self.report.warn(format!(
+                "The entry '{}' has no date! This can cause issues with templates and feeds.",
+                entry.location.source_child_path.display()
+            ))?;
+        }
+        Ok(entries)
+    }
 
-                // 2. Sort the collection ascending by date
-                // TODO: Make this a optional feature
-                entries.sort_by(|a, b| b.meta.date.cmp(&a.meta.date));
+    /// Loads a content or template file with the dates of the git history
+    fn load_entry(
+        &self,
+        path: &PathBuf,
+        extension: &str,
+        file_stem: Option<&str>,
+        binding: Option<CollectionBinding>,
+    ) -> Result<Entry> {
+        Entry::load(
+            Location::new(path, self.source_dir, self.out_dir, extension, file_stem)?,
+            binding,
+            self.config,
+            self.state.git_dates(),
+        )
+    }
 
-                // 3. Generate templates
-                if let Some(template_path) = &collection_cfg.template {
-                    for entry in entries.iter() {
-                        generate_template(&template_path, entry, &renderer)?;
-                    }
-                }
+    /// Copies the audio files in the collection, other files of collections are not part of the
+    /// output
+    fn plan_audio_files(&mut self, collection: &Collection) -> Result<()> {
+        for entry in collection.entries.iter() {
+            let Some(audio) = entry.meta.custom_str("audio") else {
+                continue;
+            };
+            let audio_path = audio_child_path(audio, entry);
+            let in_collection = audio_path.starts_with(&collection.child_dir)
+                && !audio_path.components().any(|c| c == Component::ParentDir);
+            if audio.starts_with('/') || !in_collection {
+                continue;
+            }
+            let target_path = self.out_dir.join(&audio_path);
+            self.create_parent_dirs(&target_path)?;
+            if self
+                .state
+                .is_outdated(&target_path, vec![audio_path.clone()])?
+            {
+                self.tasks.push(Task::Copy {
+                    source_path: self.source_dir.join(audio_path),
+                    target_path,
+                });
+            }
+        }
+        Ok(())
+    }
 
-                // 4. Generate index and other connections
-                let binding = CollectionBinding::new(entries.clone(), &collection_cfg);
-                // Standard index connection
-                let index_path = collection_dir
-                    .join(consts::INDEX_SOURCE_FS)
-                    .with_extension(&config.content_ext);
-                if index_path.exists() {
-                    let entry = Entry::load(
-                        Location::new(
-                            &index_path,
-                            &source_dir,
-                            &out_dir,
-                            &config.target_ext,
-                            Some(consts::INDEX_TARGET_FS),
-                        )?,
-                        Some(binding.clone()),
+    /// Plans the index of a collection, paginated when configured, and its custom connections
+    fn plan_connections(
+        &mut self,
+        collection_dir: &Path,
+        collection: &Collection,
+        binding: &CollectionBinding,
+    ) -> Result<()> {
+        // Standard index connection
+        let index_path = collection_dir
+            .join(consts::INDEX_SOURCE_FS)
+            .with_extension(&self.config.content_ext);
+        if index_path.exists() {
+            let entry = self.load_entry(
+                &index_path,
+                &self.config.target_ext,
+                Some(consts::INDEX_TARGET_FS),
+                Some(binding.clone()),
+            )?;
+            if self.options.is_published(&entry.meta) {
+                let pages = match collection.cfg.paginate_by {
+                    Some(page_size) => entry.paginate(page_size, self.out_dir)?,
+                    None => vec![entry],
+                };
+                for page in pages {
+                    self.create_parent_dirs(&page.location.target_path)?;
+                    self.plan_inclusive_template(
+                        page,
+                        collection.inputs.clone(),
+                        self.config.template.as_ref(),
                     )?;
-                    generate_inclusive_template(&entry, &renderer)?;
-                }
-                // Custom connections
-                for conn_path in collection_cfg.connections.iter() {
-                    let conn_path = source_dir.join(conn_path);
-                    let content = Entry::load(
-                        Location::new(
-                            &conn_path,
-                            &source_dir,
-                            &out_dir,
-                            &config.template_ext,
-                            None,
-                        )?,
-                        Some(binding.clone()),
-                    )?;
-                    generate_inclusive_template(&content, &renderer)?;
                 }
+            }
+        }
+        // Custom connections
+        for conn_path in collection.cfg.connections.iter() {
+            let conn_path = self.source_dir.join(conn_path);
+            let content = self.load_entry(
+                &conn_path,
+                &self.config.template_ext,
+                None,
+                Some(binding.clone()),
+            )?;
+            if self.options.is_published(&content.meta) {
+                self.plan_inclusive_template(
+                    content,
+                    collection.inputs.clone(),
+                    self.config.template.as_ref(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Plans the page listing the terms of a taxonomy, the page of every term and the feed of
+    /// every term, taxonomies without terms have no pages
+    fn plan_taxonomy(
+        &mut self,
+        kind: TaxonomyKind,
+        taxonomy: &Arc<Taxonomy>,
+        collection: &Collection,
+    ) -> Result<()> {
+        let taxonomy_cfg = taxonomy_config(kind, &collection.cfg, self.config);
+        let child_dir = collection.child_dir.join(kind.name());
+        if taxonomy.terms.is_empty() {
+            return Ok(());
+        }
+        let taxonomy_dir = self.out_dir.join(&child_dir);
+        let mut pages = Vec::new();
+        if let Some(template_path) = &taxonomy_cfg.list_template {
+            let target_path = taxonomy_dir
+                .join(consts::INDEX_TARGET_FS)
+                .with_extension(&self.config.target_ext);
+            let page = TaxonomyPage {
+                taxonomy: Arc::clone(taxonomy),
+                term: None,
+            };
+            pages.push((template_path, target_path, page));
+        }
+        if let Some(template_path) = &taxonomy_cfg.term_template {
+            for term in taxonomy.terms.values() {
+                let target_path = taxonomy_dir
+                    .join(&term.slug)
+                    .join(consts::INDEX_TARGET_FS)
+                    .with_extension(&self.config.target_ext);
+                let page = TaxonomyPage {
+                    taxonomy: Arc::clone(taxonomy),
+                    term: Some(term.slug.clone()),
+                };
+                pages.push((template_path, target_path, page));
+            }
+        }
 
-                // 5. Generate RSS if enabled
-                if let Some(rss_path) = &collection_cfg.rss {
-                    let rss_out = out_dir.join(rss_path);
-                    generate_rss_feed(&entries, rss_path, &rss_out, &config, &collection_cfg)?;
+        for (template_path, target_path, page) in pages {
+            self.sitemap.push(SitemapPage {
+                route: format!("/{}", target_path.strip_prefix(self.out_dir)?.display()),
+                lastmod: None,
+            });
+            self.create_parent_dirs(&target_path)?;
+            let mut inputs = collection.inputs.clone();
+            inputs.append(&mut self.renderer.dependencies(template_path));
+            if self.state.is_outdated(&target_path, inputs)? {
+                self.tasks.push(Task::Taxonomy {
+                    template_path: template_path.clone(),
+                    page,
+                    target_path,
+                });
+            }
+        }
+
+        if taxonomy_cfg.rss.unwrap_or(false) {
+            for term in taxonomy.terms.values() {
+                let rss_path = child_dir.join(&term.slug).join(consts::TERM_RSS_FN);
+                let rss_out = self.out_dir.join(&rss_path);
+                self.create_parent_dirs(&rss_out)?;
+                if self
+                    .state
+                    .is_outdated(&rss_out, collection.inputs.clone())?
+                {
+                    self.tasks.push(Task::Feed {
+                        format: FeedFormat::Rss,
+                        entries: feed_entries(
+                            term.entries
+                                .iter()
+                                .map(|entry| &collection.entries[entry.index]),
+                            &collection.cfg,
+                        ),
+                        feed_path: rss_path,
+                        feed_out: rss_out,
+                        collection_cfg: Box::new(collection.cfg.clone()),
+                        term: Some(term.name.clone()),
+                    });
                 }
             }
         }
+        Ok(())
+    }
+
+    /// Plans the configured RSS, Atom and JSON feeds of a collection
+    fn plan_feeds(&mut self, collection: &Collection) -> Result<()> {
+        let feeds = [
+            (FeedFormat::Rss, &collection.cfg.rss),
+            (FeedFormat::Atom, &collection.cfg.atom),
+            (FeedFormat::Json, &collection.cfg.json_feed),
+        ];
+        for (format, feed_path) in feeds {
+            let Some(feed_path) = feed_path else {
+                continue;
+            };
+            let feed_out = self.out_dir.join(feed_path);
+            if self
+                .state
+                .is_outdated(&feed_out, collection.inputs.clone())?
+            {
+                self.tasks.push(Task::Feed {
+                    format,
+                    entries: feed_entries(&collection.entries, &collection.cfg),
+                    feed_path: feed_path.clone(),
+                    feed_out,
+                    collection_cfg: Box::new(collection.cfg.clone()),
+                    term: None,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Plans the sitemap, which is split up into multiple sitemaps listed in a sitemap index
+    /// when it has too many URLs
+    ///
+    /// The sitemap is always regenerated since it depends on every page.
+    fn plan_sitemap(&mut self) -> Result<()> {
+        let sitemap_path = self.out_dir.join(consts::SITEMAP_FN);
+        if self.state.is_generated(&sitemap_path) {
+            return self.report.warn(format!(
+                "The sitemap was not generated because the file '{}' already exists in the source directory",
+                consts::SITEMAP_FN
+            ));
+        }
+        let mut urls: Vec<SitemapUrl> = std::mem::take(&mut self.sitemap)
+            .into_iter()
+            .map(|page| SitemapUrl {
+                loc: self.config.base_url.clone() + &page.route,
+                lastmod: page.lastmod.map(|date| date.to_rfc3339()),
+            })
+            .collect();
+        urls.sort_by(|a, b| a.loc.cmp(&b.loc));
+        urls.dedup_by(|a, b| a.loc == b.loc);
+
+        if urls.len() <= sitemap::MAX_URLS {
+            self.state.is_outdated(&sitemap_path, Vec::new())?;
+            self.tasks.push(Task::Sitemap {
+                urls,
+                target_path: sitemap_path,
+            });
+            return Ok(());
+        }
+        let mut sitemaps = Vec::new();
+        for (index, chunk) in urls.chunks(sitemap::MAX_URLS).enumerate() {
+            let file_name = format!("sitemap-{}.xml", index + 1);
+            let target_path = self.out_dir.join(&file_name);
+            self.state.is_outdated(&target_path, Vec::new())?;
+            self.tasks.push(Task::Sitemap {
+                urls: chunk.to_vec(),
+                target_path,
+            });
+            sitemaps.push(SitemapRef {
+                loc: format!("{}/{file_name}", self.config.base_url),
+            });
+        }
+        self.state.is_outdated(&sitemap_path, Vec::new())?;
+        self.tasks.push(Task::SitemapIndex {
+            sitemaps,
+            target_path: sitemap_path,
+        });
+        Ok(())
+    }
+
+    /// Creates the parent directories of an output that don't exist in the source directory,
+    /// like the directories of paginated pages
+    fn create_parent_dirs(&mut self, output: &Path) -> Result<()> {
+        let mut dirs = Vec::new();
+        let mut parent = output.parent();
+        while let Some(dir) =
+            parent.filter(|dir| dir != self.out_dir && !self.state.is_generated(dir))
+        {
+            dirs.push(dir);
+            parent = dir.parent();
+        }
+        for dir in dirs.into_iter().rev() {
+            if self.state.is_outdated(dir, Vec::new())? {
+                create_output_dir(dir, self.options)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Plans the rendering of an entry with the template in its meta, or `default_template` when
+    /// the meta doesn't set one
+    fn plan_inclusive_template(
+        &mut self,
+        entry: Entry,
+        inputs: Vec<PathBuf>,
+        default_template: Option<&PathBuf>,
+    ) -> Result<()> {
+        let template_path = entry
+            .meta
+            .template
+            .clone()
+            .or_else(|| default_template.cloned())
+            .with_context(|| {
+                format!(
+                    "Unspecified required template option for '{}'",
+                    entry.location.source_child_path.display()
+                )
+            })?;
+        self.plan_template(&template_path, entry, inputs)
     }
-    info!("Generation successfully completed!");
 
+    /// Plans the rendering of an entry with a template, if the entry, template or other inputs
+    /// changed
+    fn plan_template(
+        &mut self,
+        template_path: &Path,
+        entry: Entry,
+        mut inputs: Vec<PathBuf>,
+    ) -> Result<()> {
+        // Pages can be excluded from the sitemap with `sitemap: false`
+        if entry.meta.custom_field::<bool>("sitemap")? != Some(false) {
+            self.sitemap.push(SitemapPage {
+                route: entry.location.route.to_string_lossy().to_string(),
+                lastmod: entry.meta.updated.or(entry.meta.date),
+            });
+        }
+        inputs.push(entry.location.source_child_path.clone());
+        inputs.append(&mut self.renderer.dependencies(template_path));
+        if self
+            .state
+            .is_outdated(&entry.location.target_path, inputs)?
+        {
+            self.tasks.push(Task::Template {
+                template_path: template_path.to_path_buf(),
+                target_path: entry.location.target_path.clone(),
+                entry: Some(Box::new(entry)),
+            });
+        } else {
+            trace!(
+                "Skip unchanged content '{}'",
+                entry.location.source_child_path.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Creates a directory in the output directory, unless the site is only checked
+fn create_output_dir(dir: &Path, options: &BuildOptions) -> Result<()> {
+    if !options.check {
+        trace!("Create direcory '{}'", dir.display());
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory '{}'", dir.display()))?;
+    }
     Ok(())
 }
 
-fn generate_inclusive_template(entry: &Entry, renderer: &ContentRenderer) -> Result<()> {
-    let template_path = entry.meta.template.as_ref().context(format!(
-        "Unspecified required template option for '{}'",
-        entry.location.source_child_path.display()
-    ))?;
-    generate_template(template_path, entry, renderer)
+/// Writes a generated output, unless the site is only checked
+fn write_output(path: &Path, contents: impl AsRef<[u8]>, options: &BuildOptions) -> Result<()> {
+    if !options.check {
+        fs::write(path, contents)
+            .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    }
+    Ok(())
 }
 
-fn generate_template(
+fn render_template(
     template_path: &PathBuf,
     entry: &Entry,
     renderer: &ContentRenderer,
-) -> Result<()> {
+) -> Result<String> {
     trace!(
         "Generate content for {}",
         entry.location.source_child_path.display()
     );
-    let html = renderer
+    renderer
         .render(&template_path, Some(entry))
         .with_context(|| {
             format!(
@@ -244,62 +1044,327 @@ fn generate_template(
                 entry.location.source_child_path.display(),
                 template_path.display(),
             )
-        })?;
-    fs::write(&entry.location.target_path, html).with_context(|| {
-        format!(
-            "Failed to write generated content to {}",
-            entry.location.target_path.display()
-        )
-    })?;
-    Ok(())
+        })
 }
 
 fn generate_rss_feed(
     entries: &[Entry],
     rss_path: &PathBuf,
-    rss_out_path: &PathBuf,
+    source_dir: &Path,
     main_cfg: &Config,
     collection_cfg: &CollectionConfig,
-) -> Result<()> {
+    term: Option<&str>,
+) -> Result<String> {
     trace!("Generating RSS feed '{}'", rss_path.display());
     let has_content = collection_cfg.template.is_some();
     let mut rss_items = Vec::new();
     for entry in entries {
-        let link = {
-            if has_content {
-                main_cfg.base_url.clone() + entry.location.short_route.to_str().unwrap()
-            } else {
-                main_cfg.base_url.clone() + "#" + &entry.location.target_file_stem
-            }
+        let link = entry_link(entry, main_cfg, has_content);
+        // The full content is in content:encoded, so the description can be a summary
+        let (description, content) = match collection_cfg.feed_content {
+            FeedContent::Full => (entry_summary(entry), Some(entry.content.to_string())),
+            _ => (entry_description(entry, collection_cfg), None),
         };
-        let pub_date = {
-            if let Some(date) = entry.meta.date {
-                date.and_time(NaiveTime::from_hms(12, 0, 0))
-            } else {
-                warn!("The entry {} item has no date! This can cause issues with templates and RSS feed generation.", entry.meta.title);
-                NaiveDateTime::from_timestamp(0, 0)
-            }
+        // RSS requires an email address for the author, names are in dc:creator
+        let author = entry
+            .meta
+            .custom_str("author")
+            .map(String::from)
+            .or(collection_cfg.author.clone())
+            .or(main_cfg.author.clone());
+        let (author, creator) = match author {
+            Some(author) if author.contains('@') => (Some(author), None),
+            author => (None, author),
+        };
+        let categories = entry
+            .meta
+            .category
+            .iter()
+            .chain(entry.meta.tags.iter().flatten())
+            .map(|category| RssCategory {
+                value: category.clone(),
+            })
+            .collect();
+        let enclosure = match entry.meta.custom_field::<RssEnclosure>("enclosure")? {
+            Some(enclosure) => Some(RssEnclosure {
+                url: absolute_url(&enclosure.url, main_cfg),
+                ..enclosure
+            }),
+            None => match entry.meta.custom_str("audio") {
+                Some(audio) => Some(audio_enclosure(audio, entry, source_dir, main_cfg)?),
+                None => None,
+            },
+        };
+        // The iTunes fields need the namespace, which is only declared for podcasts
+        let (duration, episode) = match collection_cfg.podcast {
+            Some(_) => (
+                // Durations can be written as seconds or as a time
+                entry
+                    .meta
+                    .custom_field::<serde_yaml::Value>("duration")?
+                    .and_then(|duration| match duration {
+                        serde_yaml::Value::Number(seconds) => Some(seconds.to_string()),
+                        serde_yaml::Value::String(time) => Some(time),
+                        _ => None,
+                    }),
+                entry.meta.custom_field("episode")?,
+            ),
+            None => (None, None),
         };
         rss_items.push(RssItem {
             title: entry.meta.title.clone(),
             link: link.clone(),
-            description: entry.content.to_string(),
+            description,
+            content,
+            author,
+            creator,
+            categories,
+            enclosure,
             guid: RssGuid {
                 value: link,
                 is_permalink: has_content,
             },
-            pub_date,
+            itunes_duration: duration,
+            itunes_episode: episode,
+            pub_date: entry.meta.date,
         })
     }
     let link = format!("{}/{}", main_cfg.base_url, rss_path.display());
-    let channel = RssChannel::new(
-        collection_cfg.title.clone(),
+    let mut channel = RssChannel::new(
+        feed_title(collection_cfg, term),
         link,
         collection_cfg.description.clone(),
         rss_items,
     );
-    let feed = RssFeed::from_channel(channel);
-    let rss_str = rss::to_str(feed)?;
-    fs::write(rss_out_path, rss_str)?;
-    Ok(())
+    let feed = {
+        if let Some(podcast) = &collection_cfg.podcast {
+            channel.itunes_image = Some(ItunesImage {
+                href: absolute_url(&podcast.image, main_cfg),
+            });
+            channel.itunes_author = Some(
+                podcast
+                    .author
+                    .clone()
+                    .unwrap_or_else(|| feed_author(main_cfg, collection_cfg)),
+            );
+            channel.itunes_category = Some(ItunesCategory {
+                text: podcast.category.clone(),
+            });
+            channel.itunes_explicit = Some(podcast.explicit);
+            let mut feed = RssFeed::from_channel(channel);
+            feed.itunes_namespace = Some(rss::ITUNES_NAMESPACE.to_string());
+            feed
+        } else {
+            RssFeed::from_channel(channel)
+        }
+    };
+    rss::to_str(feed)
+}
+
+fn generate_atom_feed(
+    entries: &[Entry],
+    atom_path: &Path,
+    main_cfg: &Config,
+    collection_cfg: &CollectionConfig,
+    term: Option<&str>,
+) -> Result<String> {
+    trace!("Generating Atom feed '{}'", atom_path.display());
+    let has_content = collection_cfg.template.is_some();
+    let mut atom_entries = Vec::new();
+    for entry in entries {
+        let link = entry_link(entry, main_cfg, has_content);
+        let categories = entry
+            .meta
+            .category
+            .iter()
+            .chain(entry.meta.tags.iter().flatten())
+            .map(|term| AtomCategory { term: term.clone() })
+            .collect();
+        atom_entries.push(AtomEntry {
+            title: entry.meta.title.clone(),
+            id: link.clone(),
+            updated: entry_updated(entry),
+            published: entry.meta.date,
+            link: AtomLink::new(link, "alternate"),
+            categories,
+            summary: (collection_cfg.feed_content != FeedContent::Full)
+                .then(|| AtomContent::html(entry_description(entry, collection_cfg))),
+            content: (collection_cfg.feed_content == FeedContent::Full)
+                .then(|| AtomContent::html(entry.content.to_string())),
+        })
+    }
+    let link = format!("{}/{}", main_cfg.base_url, atom_path.display());
+    let feed = AtomFeed::new(
+        feed_title(collection_cfg, term),
+        collection_cfg.description.clone(),
+        link,
+        feed_home_page(atom_path, main_cfg),
+        feed_author(main_cfg, collection_cfg),
+        atom_entries,
+    );
+    atom::to_str(feed)
+}
+
+fn generate_json_feed(
+    entries: &[Entry],
+    json_feed_path: &Path,
+    main_cfg: &Config,
+    collection_cfg: &CollectionConfig,
+    term: Option<&str>,
+) -> Result<String> {
+    trace!("Generating JSON feed '{}'", json_feed_path.display());
+    let has_content = collection_cfg.template.is_some();
+    let items = entries
+        .iter()
+        .map(|entry| {
+            let link = entry_link(entry, main_cfg, has_content);
+            JsonFeedItem {
+                id: link.clone(),
+                url: link,
+                title: entry.meta.title.clone(),
+                content_html: entry_description(entry, collection_cfg),
+                date_published: entry.meta.date,
+                date_modified: entry.meta.updated,
+                tags: entry.meta.tags.clone().unwrap_or_default(),
+            }
+        })
+        .collect();
+    let feed = JsonFeed::new(
+        feed_title(collection_cfg, term),
+        feed_home_page(json_feed_path, main_cfg),
+        format!("{}/{}", main_cfg.base_url, json_feed_path.display()),
+        collection_cfg.description.clone(),
+        vec![JsonFeedAuthor {
+            name: feed_author(main_cfg, collection_cfg),
+        }],
+        items,
+    );
+    json_feed::to_str(feed)
+}
+
+/// Page a feed belongs to, which is the page of the directory it is in
+fn feed_home_page(feed_path: &Path, main_cfg: &Config) -> String {
+    match feed_path.parent() {
+        Some(parent) if parent != Path::new("") => {
+            format!("{}/{}/", main_cfg.base_url, parent.display())
+        }
+        _ => format!("{}/", main_cfg.base_url),
+    }
+}
+
+/// Author of the feeds of a collection, the collection itself if no author is configured
+fn feed_author(main_cfg: &Config, collection_cfg: &CollectionConfig) -> String {
+    collection_cfg
+        .author
+        .clone()
+        .or(main_cfg.author.clone())
+        .unwrap_or(collection_cfg.title.clone())
+}
+
+/// Latest entries of a collection that are included in its feeds
+fn feed_entries<'a>(
+    entries: impl IntoIterator<Item = &'a Entry>,
+    collection_cfg: &CollectionConfig,
+) -> Vec<Entry> {
+    let limit = collection_cfg.feed_limit.unwrap_or(usize::MAX);
+    entries.into_iter().take(limit).cloned().collect()
+}
+
+/// Configuration of a taxonomy in a collection, with the defaults of the site configuration
+fn taxonomy_config(
+    kind: TaxonomyKind,
+    collection_cfg: &CollectionConfig,
+    config: &Config,
+) -> TaxonomyConfig {
+    match kind {
+        TaxonomyKind::Tags => collection_cfg.tags.or(&config.tags),
+        TaxonomyKind::Categories => collection_cfg.categories.or(&config.categories),
+    }
+}
+
+/// Description of an entry in a feed, which is the content or a summary of it
+fn entry_description(entry: &Entry, collection_cfg: &CollectionConfig) -> String {
+    match collection_cfg.feed_content {
+        FeedContent::Full => entry.content.to_string(),
+        FeedContent::Excerpt => entry.excerpt().to_string(),
+        FeedContent::Description => entry_summary(entry),
+    }
+}
+
+/// The description in the front matter of an entry, or an excerpt of the content without one
+fn entry_summary(entry: &Entry) -> String {
+    entry
+        .meta
+        .custom_str("description")
+        .map(String::from)
+        .unwrap_or_else(|| entry.excerpt().to_string())
+}
+
+/// Enclosure of a local audio file, `audio` is a path relative to the entry or, when it starts
+/// with a `/`, to the source directory. Files in the collection are copied when planning it.
+fn audio_enclosure(
+    audio: &str,
+    entry: &Entry,
+    source_dir: &Path,
+    main_cfg: &Config,
+) -> Result<RssEnclosure> {
+    let child_path = audio_child_path(audio, entry);
+    let length = fs::metadata(source_dir.join(&child_path))
+        .with_context(|| format!("Failed to read audio file '{}'", child_path.display()))?
+        .len();
+    Ok(RssEnclosure {
+        url: format!("{}/{}", main_cfg.base_url, child_path.display()),
+        length,
+        mime_type: mime::content_type(&child_path).to_string(),
+    })
+}
+
+/// Source child path of the audio file of an entry
+fn audio_child_path(audio: &str, entry: &Entry) -> PathBuf {
+    match audio.strip_prefix('/') {
+        Some(path) => PathBuf::from(path),
+        None => entry
+            .location
+            .source_child_path
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(audio),
+    }
+}
+
+/// Makes routes like `/episodes/1.mp3` absolute using the base URL, full URLs are kept
+fn absolute_url(url: &str, main_cfg: &Config) -> String {
+    if url.starts_with('/') {
+        main_cfg.base_url.clone() + url
+    } else {
+        url.to_string()
+    }
+}
+
+/// Title of a feed of a collection or of a term of one of its taxonomies
+fn feed_title(collection_cfg: &CollectionConfig, term: Option<&str>) -> String {
+    match term {
+        Some(term) => format!("{} - {term}", collection_cfg.title),
+        None => collection_cfg.title.clone(),
+    }
+}
+
+/// Link to an entry in a feed, entries without their own page link to the collection page
+fn entry_link(entry: &Entry, main_cfg: &Config, has_content: bool) -> String {
+    if has_content {
+        main_cfg.base_url.clone() + entry.location.short_route.to_str().unwrap()
+    } else {
+        main_cfg.base_url.clone() + "#" + &entry.location.target_file_stem
+    }
+}
+
+/// Last change of an entry in Atom feeds, which require one: the updated or publication date,
+/// or the modification time of the source file for entries without dates
+fn entry_updated(entry: &Entry) -> DateTime<FixedOffset> {
+    entry.meta.updated.or(entry.meta.date).unwrap_or_else(|| {
+        let modified = fs::metadata(&entry.location.source_path)
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or_else(|_| SystemTime::now());
+        DateTime::<Utc>::from(modified).trunc_subsecs(0).into()
+    })
 }
@@ -0,0 +1,210 @@
+use crate::{
+    config::{CollectionConfig, Config, TaxonomyConfig},
+    consts,
+    sources::meta::DATE_FORMAT,
+    util::date,
+};
+use anyhow::{anyhow, Context, Result};
+use log::{info, trace};
+use std::{fs, path::Path};
+
+const TEMPLATES_DIR: &str = "templates";
+
+const BASE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{% block title %}{{ meta.title }}{% endblock title %}</title>
+</head>
+<body>
+    <nav>
+        <a href="/">Home</a>
+        <a href="/posts/">Posts</a>
+    </nav>
+    <main>
+        {% block content %}{% endblock content %}
+    </main>
+</body>
+</html>
+"#;
+
+const PAGE_TEMPLATE: &str = r#"{% extends "templates/base.html" %}
+{% block content %}
+<h1>{{ meta.title }}</h1>
+{{ content }}
+{% endblock content %}
+"#;
+
+const POST_TEMPLATE: &str = r#"{% extends "templates/base.html" %}
+{% block content %}
+<article>
+    <h1>{{ meta.title }}</h1>
+    {% if meta.date %}<time datetime="{{ meta.date }}">{{ meta.date | date(format="%Y-%m-%d") }}</time>{% endif %}
+    {{ content }}
+    {% if meta.tags %}<p>Tags: {{ meta.tags | join(sep=", ") }}</p>{% endif %}
+</article>
+{% endblock content %}
+"#;
+
+const LIST_TEMPLATE: &str = r#"{% extends "templates/base.html" %}
+{% block content %}
+<h1>{{ meta.title }}</h1>
+{{ content }}
+<ul>
+    {% for entry in collection.entries %}
+    <li>
+        <a href="{{ entry.location.short_route }}">{{ entry.meta.title }}</a>
+        {% if entry.meta.date %}<time datetime="{{ entry.meta.date }}">{{ entry.meta.date | date(format="%Y-%m-%d") }}</time>{% endif %}
+    </li>
+    {% endfor %}
+</ul>
+{% if collection.paginator %}
+<nav>
+    {% if collection.paginator.previous %}<a href="{{ collection.paginator.previous }}">Newer posts</a>{% endif %}
+    {% if collection.paginator.next %}<a href="{{ collection.paginator.next }}">Older posts</a>{% endif %}
+</nav>
+{% endif %}
+{% if collection.rss %}<a href="{{ collection.rss.route }}">RSS feed</a>{% endif %}
+{% endblock content %}
+"#;
+
+const TAGS_TEMPLATE: &str = r#"{% extends "templates/base.html" %}
+{% block title %}Tags{% endblock title %}
+{% block content %}
+<h1>Tags</h1>
+<ul>
+    {% for slug, term in taxonomy.terms %}
+    <li><a href="{{ term.route }}">{{ term.name }}</a> ({{ term.entries | length }})</li>
+    {% endfor %}
+</ul>
+{% endblock content %}
+"#;
+
+const TAG_TEMPLATE: &str = r#"{% extends "templates/base.html" %}
+{% block title %}{{ term.name }}{% endblock title %}
+{% block content %}
+<h1>Posts tagged '{{ term.name }}'</h1>
+<ul>
+    {% for entry in term.entries %}
+    <li><a href="{{ entry.route }}">{{ entry.title }}</a></li>
+    {% endfor %}
+</ul>
+{% if term.rss %}<a href="{{ term.rss }}">RSS feed</a>{% endif %}
+<a href="{{ taxonomy.route }}">All tags</a>
+{% endblock content %}
+"#;
+
+const HOME_PAGE: &str = r#"---
+title: Home
+template: templates/page.html
+---
+Welcome to your new site! Have a look at the [posts](/posts/).
+"#;
+
+const POSTS_INDEX: &str = r#"---
+title: Posts
+template: templates/list.html
+---
+"#;
+
+const POSTS_ARCHETYPE: &str = r#"---
+category: general
+tags: []
+---
+Write your post here.
+"#;
+
+/// Creates a starter site in an empty or new directory
+pub fn init(directory: &Path) -> Result<()> {
+    if directory.exists()
+        && directory
+            .read_dir()
+            .with_context(|| format!("Failed to read directory '{}'", directory.display()))?
+            .next()
+            .is_some()
+    {
+        return Err(anyhow!(
+            "The directory '{}' is not empty!",
+            directory.display()
+        ));
+    }
+
+    let config = Config {
+        ignore_paths: vec![consts::CONFIG_FN.into(), TEMPLATES_DIR.into()],
+        ..Default::default()
+    };
+    let collection_config = CollectionConfig {
+        title: String::from("Posts"),
+        description: String::from("All posts"),
+        template: Some(Path::new(TEMPLATES_DIR).join("post.html")),
+        rss: Some("posts/rss.xml".into()),
+        tags: TaxonomyConfig {
+            list_template: Some(Path::new(TEMPLATES_DIR).join("tags.html")),
+            term_template: Some(Path::new(TEMPLATES_DIR).join("tag.html")),
+            rss: Some(true),
+        },
+        ..Default::default()
+    };
+    let sample_post = format!(
+        "---\ntitle: Hello world\ndate: {}\ncategory: general\ntags: [renatic]\n---\nThis is the first post of your new site.\n",
+        date::today(config.timezone).format(DATE_FORMAT)
+    );
+
+    let files = [
+        (
+            consts::CONFIG_FN.to_string(),
+            serde_yaml::to_string(&config)?,
+        ),
+        (
+            format!("{TEMPLATES_DIR}/base.html"),
+            BASE_TEMPLATE.to_string(),
+        ),
+        (
+            format!("{TEMPLATES_DIR}/page.html"),
+            PAGE_TEMPLATE.to_string(),
+        ),
+        (
+            format!("{TEMPLATES_DIR}/post.html"),
+            POST_TEMPLATE.to_string(),
+        ),
+        (
+            format!("{TEMPLATES_DIR}/list.html"),
+            LIST_TEMPLATE.to_string(),
+        ),
+        (
+            format!("{TEMPLATES_DIR}/tags.html"),
+            TAGS_TEMPLATE.to_string(),
+        ),
+        (
+            format!("{TEMPLATES_DIR}/tag.html"),
+            TAG_TEMPLATE.to_string(),
+        ),
+        (String::from("index.md"), HOME_PAGE.to_string()),
+        (
+            format!("posts/{}", consts::COLLECTION_CONFIG_FN),
+            serde_yaml::to_string(&collection_config)?,
+        ),
+        (
+            format!("posts/{}.md", consts::INDEX_SOURCE_FS),
+            POSTS_INDEX.to_string(),
+        ),
+        (
+            format!("posts/{}.md", consts::ARCHETYPE_SOURCE_FS),
+            POSTS_ARCHETYPE.to_string(),
+        ),
+        (String::from("posts/hello-world.md"), sample_post),
+    ];
+    for (path, contents) in files {
+        let path = directory.join(path);
+        trace!("Create '{}'", path.display());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, contents)
+            .with_context(|| format!("Failed to write '{}'", path.display()))?;
+    }
+
+    info!("Created a new site in '{}'", directory.display());
+    Ok(())
+}
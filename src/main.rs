@@ -1,16 +1,31 @@
+mod archetype;
+mod cache;
 mod config;
 mod consts;
+mod diagnostic;
 mod generator;
 mod index;
+mod init;
 mod sources;
 mod renderer;
+mod server;
 mod util;
+mod watcher;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
+use config::Config;
+use generator::{BuildOptions, Generator};
 use log::error;
+use server::LiveReload;
 use util::minifier::MinificationLevel;
-use std::path::{Path, PathBuf};
+use std::{
+    collections::hash_map::DefaultHasher,
+    env,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    process, thread,
+};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -23,8 +38,64 @@ struct Cli {
     verbose: bool,
 }
 
+#[derive(clap::Args)]
+struct BuildArgs {
+    /// Set the minification level
+    #[clap(long, short = 'm', arg_enum, default_value_t = MinificationLevel::SpecCompliant)]
+    minification: MinificationLevel,
+
+    /// Ignore the build cache and regenerate everything
+    #[clap(long)]
+    clean: bool,
+
+    /// Include content marked as draft
+    #[clap(long)]
+    drafts: bool,
+
+    /// Include content with a date in the future
+    #[clap(long)]
+    future: bool,
+
+    /// Continue after errors and report all of them at the end
+    #[clap(long, short = 'k')]
+    keep_going: bool,
+
+    /// Treat warnings, like entries without a date, as errors
+    #[clap(long)]
+    strict: bool,
+}
+
+impl BuildArgs {
+    fn options(self, live_reload: bool) -> BuildOptions {
+        BuildOptions {
+            mfc_level: self.minification,
+            live_reload,
+            clean: self.clean,
+            drafts: self.drafts,
+            future: self.future,
+            keep_going: self.keep_going,
+            strict: self.strict,
+            check: false,
+        }
+    }
+}
+
 #[derive(clap::Subcommand)]
 enum Commands {
+    /// Create a new site with a starter layout
+    Init {
+        /// Directory to create the site in, must be empty or not exist
+        directory: PathBuf,
+    },
+    /// Create a new content entry in a collection
+    New {
+        /// Path of the entry without extension, like 'posts/my-first-post'
+        path: PathBuf,
+
+        /// Select a source directory, defaults to current directory
+        #[clap(long, short = 's')]
+        source: Option<PathBuf>,
+    },
     /// Generate a site
     Generate {
         /// Path to the output directory
@@ -34,9 +105,47 @@ enum Commands {
         #[clap(long, short = 's')]
         source: Option<PathBuf>,
 
-        /// Set the minification level
-        #[clap(long, short = 'm', arg_enum, default_value_t = MinificationLevel::SpecCompliant)]
-        minification: MinificationLevel,
+        /// Regenerate the site when the source directory changes
+        #[clap(long, short = 'w')]
+        watch: bool,
+
+        #[clap(flatten)]
+        build: BuildArgs,
+    },
+    /// Check a site for errors by rendering it in memory, without writing any output
+    Check {
+        /// Select a source directory, defaults to current directory
+        #[clap(long, short = 's')]
+        source: Option<PathBuf>,
+
+        /// Include content marked as draft
+        #[clap(long)]
+        drafts: bool,
+
+        /// Include content with a date in the future
+        #[clap(long)]
+        future: bool,
+
+        /// Treat warnings, like entries without a date, as errors
+        #[clap(long)]
+        strict: bool,
+    },
+    /// Generate a site into a temporary directory and serve it on a local HTTP server
+    Serve {
+        /// Select a source directory, defaults to current directory
+        #[clap(long, short = 's')]
+        source: Option<PathBuf>,
+
+        /// Port of the HTTP server
+        #[clap(long, short = 'p', default_value_t = 8000)]
+        port: u16,
+
+        /// Regenerate the site when the source directory changes
+        #[clap(long, short = 'w')]
+        watch: bool,
+
+        #[clap(flatten)]
+        build: BuildArgs,
     },
 }
 
@@ -57,22 +166,95 @@ fn main() -> Result<()> {
         .init();
 
     match cli.command {
+        Commands::Init { directory } => init::init(&directory)?,
+        Commands::New { path, source } => {
+            let source_dir = source_dir(source)?;
+            let config = Config::load(&source_dir.join(consts::CONFIG_FN))?;
+            archetype::create_entry(&source_dir, &config, &path)?;
+        }
         Commands::Generate {
             source,
             output,
-            minification,
+            watch,
+            build,
         } => {
-            let source_dir = source
-                .unwrap_or(Path::new(".").to_path_buf())
-                .canonicalize()?;
+            let source_dir = source_dir(source)?;
             let out_dir = output.canonicalize()?;
             if source_dir == out_dir {
                 error!("The source directory can't be the destination directory!");
             } else {
-                generator::generate(&source_dir, &out_dir, &minification)?;
+                let generator = Generator::load(&source_dir, &out_dir, build.options(false))?;
+                if watch {
+                    watcher::watch(generator, || {})?;
+                } else {
+                    generator.generate()?;
+                }
+            }
+        }
+        Commands::Check {
+            source,
+            drafts,
+            future,
+            strict,
+        } => {
+            let source_dir = source_dir(source)?;
+            let options = BuildOptions {
+                mfc_level: MinificationLevel::SpecCompliant,
+                live_reload: false,
+                clean: true,
+                drafts,
+                future,
+                keep_going: true,
+                strict,
+                check: true,
+            };
+            // The output paths are only planned in the preview directory, nothing is written
+            let generator = Generator::load(&source_dir, &preview_dir(&source_dir), options)?;
+            generator.generate()?;
+        }
+        Commands::Serve {
+            source,
+            port,
+            watch,
+            build,
+        } => {
+            let source_dir = source_dir(source)?;
+            let out_dir = preview_dir(&source_dir);
+            let generator = Generator::load(&source_dir, &out_dir, build.options(watch))?;
+            let target_ext = generator.config().target_ext.clone();
+            if watch {
+                let live_reload = LiveReload::default();
+                let server_reload = live_reload.clone();
+                thread::spawn(move || {
+                    if let Err(e) = server::serve(&out_dir, port, &target_ext, Some(server_reload))
+                    {
+                        error!("{e:?}");
+                        process::exit(1);
+                    }
+                });
+                watcher::watch(generator, || live_reload.reload())?;
+            } else {
+                generator.generate()?;
+                server::serve(&out_dir, port, &target_ext, None)?;
             }
         }
     }
 
     Ok(())
 }
+
+fn source_dir(source: Option<PathBuf>) -> Result<PathBuf> {
+    source
+        .unwrap_or(Path::new(".").to_path_buf())
+        .canonicalize()
+        .with_context(|| "Failed to find the source directory")
+}
+
+/// Temporary output directory for previews, unique for every source directory
+fn preview_dir(source_dir: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    source_dir.hash(&mut hasher);
+    env::temp_dir()
+        .join("renatic")
+        .join(format!("{:x}", hasher.finish()))
+}
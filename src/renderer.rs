@@ -1,33 +1,84 @@
 use crate::{
     config::Config,
+    consts::LIVE_RELOAD_ROUTE,
+    diagnostic::{offset_position, Diagnostic},
+    generator::BuildOptions,
     sources::TemplateSource,
-    util::minifier::{self, MinificationLevel},
+    util::{
+        date::DateFilter,
+        minifier::{self, MinificationLevel},
+    },
 };
 use anyhow::{Context, Result};
 use log::info;
-use std::path::PathBuf;
-use tera::{Context as TemplateContext, Tera};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs, iter,
+    path::{Path, PathBuf},
+};
+use tera::{Context as TemplateContext, Error as TemplateError, ErrorKind, Tera};
 
 #[derive(Clone)]
 pub struct ContentRenderer {
     mfc_level: MinificationLevel,
+    live_reload: bool,
     tera: Tera,
+    /// Templates referenced by every template
+    references: HashMap<String, Vec<String>>,
 }
 
 impl ContentRenderer {
-    pub fn load(
-        parent_dir: PathBuf,
-        config: &Config,
-        mfc_level: MinificationLevel,
-    ) -> Result<Self> {
+    pub fn load(parent_dir: PathBuf, config: &Config, options: &BuildOptions) -> Result<Self> {
         let dirs = format!("{}/**/*.{}", parent_dir.display(), config.template_ext);
 
         let mut tera = Tera::new(&dirs)?;
         tera.autoescape_on(vec![]);
+        tera.register_filter(
+            "date",
+            DateFilter {
+                locale: config.locale,
+                timezone: config.timezone,
+            },
+        );
 
         info!("Loaded {} template files", tera.templates.len());
 
-        Ok(Self { tera, mfc_level })
+        Ok(Self {
+            references: find_references(&tera),
+            tera,
+            mfc_level: options.mfc_level.clone(),
+            live_reload: options.live_reload,
+        })
+    }
+
+    /// Reloads all templates from disk
+    pub fn reload(&mut self) -> Result<()> {
+        self.tera
+            .full_reload()
+            .with_context(|| "Failed to reload templates")?;
+        self.references = find_references(&self.tera);
+        info!("Reloaded {} template files", self.tera.templates.len());
+        Ok(())
+    }
+
+    /// The template and all templates it extends, includes or imports, relative to the source
+    /// directory
+    pub fn dependencies(&self, template: &Path) -> Vec<PathBuf> {
+        let mut dependencies = HashSet::new();
+        let mut unvisited = vec![template.to_string_lossy().to_string()];
+        while let Some(name) = unvisited.pop() {
+            if let Some(references) = self.references.get(&name) {
+                unvisited.extend(
+                    references
+                        .iter()
+                        .filter(|reference| !dependencies.contains(*reference))
+                        .cloned(),
+                );
+            }
+            dependencies.insert(name);
+        }
+        dependencies.into_iter().map(PathBuf::from).collect()
     }
 
     pub fn render(&self, path: &PathBuf, template: Option<&dyn TemplateSource>) -> Result<String> {
@@ -38,15 +89,140 @@ impl ContentRenderer {
                 TemplateContext::default()
             }
         };
-        let html_output = self
+        let name = path.to_str().unwrap();
+        let mut html_output = self
             .tera
-            .render(path.to_str().unwrap(), &context)
-            .with_context(|| {
-                format!(
-                    "Failed to render template '{}' with context '{context:#?}'",
-                    path.display(),
-                )
-            })?;
+            .render(name, &context)
+            .map_err(|e| self.render_diagnostic(name, &e))?;
+        if self.live_reload {
+            inject_live_reload(&mut html_output);
+        }
         Ok(minifier::minify_string(&html_output, &self.mfc_level))
     }
+
+    /// Points a render error to the expression in the template that caused it, if it can be found
+    fn render_diagnostic(&self, name: &str, error: &TemplateError) -> Diagnostic {
+        let causes: Vec<&(dyn Error + 'static)> =
+            iter::successors(Some(error as &(dyn Error + 'static)), |&cause| {
+                cause.source()
+            })
+            .collect();
+        let message = causes
+            .iter()
+            .map(|cause| cause.to_string())
+            .collect::<Vec<String>>()
+            .join(": ");
+        // The innermost error that names an expression is the closest to its cause
+        let expression = causes
+            .iter()
+            .rev()
+            .filter_map(|cause| cause.downcast_ref::<TemplateError>())
+            .find_map(|error| match &error.kind {
+                ErrorKind::CallFilter(filter) => Some((filter.as_str(), true)),
+                ErrorKind::CallFunction(function)
+                | ErrorKind::CallTest(function)
+                | ErrorKind::FilterNotFound(function)
+                | ErrorKind::FunctionNotFound(function)
+                | ErrorKind::TestNotFound(function) => Some((function.as_str(), false)),
+                // Messages name variables between backticks
+                ErrorKind::Msg(message) => Some((message.split('`').nth(1)?, false)),
+                _ => None,
+            });
+        // Errors in a parent template can name the template they happened in, otherwise the
+        // template and its parents are searched for the expression
+        let names: Vec<String> = match message.split("error happened in '").nth(1) {
+            Some(rest) => rest.split('\'').take(1).map(String::from).collect(),
+            None => iter::once(name)
+                .chain(
+                    self.tera
+                        .templates
+                        .get(name)
+                        .iter()
+                        .flat_map(|template| template.parents.iter().map(String::as_str)),
+                )
+                .map(String::from)
+                .collect(),
+        };
+        let sources: Vec<(String, String)> = names
+            .into_iter()
+            .filter_map(|name| {
+                let path = self.tera.templates.get(&name)?.path.as_ref()?;
+                Some((name, fs::read_to_string(path).ok()?))
+            })
+            .collect();
+        let found = expression.and_then(|(needle, is_filter)| {
+            sources.iter().find_map(|(name, source)| {
+                let position = find_expression(source, needle, is_filter)?;
+                Some((name.as_str(), source.as_str(), position))
+            })
+        });
+        match found {
+            Some((name, source, position)) => {
+                Diagnostic::new(message, Path::new(name), source, Some(position))
+            }
+            None => Diagnostic::new(message, Path::new(name), "", None),
+        }
+    }
+}
+
+/// Line and column of the first use of a variable, function or filter in a tag of a template
+fn find_expression(source: &str, needle: &str, is_filter: bool) -> Option<(usize, usize)> {
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    source
+        .match_indices(needle)
+        .map(|(offset, _)| offset)
+        .find(|&offset| {
+            let before = &source[..offset];
+            let after = &source[offset + needle.len()..];
+            let last_open = before.rfind("{{").max(before.rfind("{%"));
+            let last_close = before.rfind("}}").max(before.rfind("%}"));
+            let in_tag = last_open > last_close;
+            let is_name = !before.ends_with(is_name_char) && !after.starts_with(is_name_char);
+            let is_filtered = !is_filter || before.trim_end().ends_with('|');
+            in_tag && is_name && is_filtered
+        })
+        .map(|offset| offset_position(source, offset))
+}
+
+fn find_references(tera: &Tera) -> HashMap<String, Vec<String>> {
+    tera.templates
+        .values()
+        .filter_map(|template| {
+            let source = fs::read_to_string(template.path.as_ref()?).ok()?;
+            Some((template.name.clone(), template_references(&source)))
+        })
+        .collect()
+}
+
+/// Names of the templates used in the `extends`, `include` and `import` tags of a template
+fn template_references(source: &str) -> Vec<String> {
+    let mut references = Vec::new();
+    for tag in source.split("{%").skip(1) {
+        let tag = tag.split("%}").next().unwrap_or_default();
+        let tag = tag.trim_start_matches('-').trim_start();
+        if ["extends", "include", "import"]
+            .iter()
+            .any(|keyword| tag.starts_with(keyword))
+        {
+            // Every second part is a quoted string
+            references.extend(
+                tag.split(['"', '\'', '`'])
+                    .skip(1)
+                    .step_by(2)
+                    .map(String::from),
+            );
+        }
+    }
+    references
+}
+
+/// Adds a script that reloads the page when the preview server sends a reload event
+fn inject_live_reload(html: &mut String) {
+    let script = format!(
+        "<script>new EventSource(\"{LIVE_RELOAD_ROUTE}\").onmessage = () => location.reload();</script>"
+    );
+    match html.to_ascii_lowercase().rfind("</body>") {
+        Some(index) => html.insert_str(index, &script),
+        None => html.push_str(&script),
+    }
 }
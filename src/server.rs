@@ -0,0 +1,299 @@
+use crate::{
+    consts::{INDEX_TARGET_FS, LIVE_RELOAD_ROUTE},
+    util::mime::content_type,
+};
+use anyhow::{Context, Result};
+use log::{info, trace, warn};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Component, Path, PathBuf},
+    sync::{
+        mpsc::{self, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Interval of keep-alive messages, used to detect closed live reload connections
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Notifies the open live reload connections of the preview server
+#[derive(Clone, Default)]
+pub struct LiveReload {
+    clients: Arc<Mutex<Vec<Sender<()>>>>,
+}
+
+impl LiveReload {
+    /// Sends a reload event to every connected page
+    pub fn reload(&self) {
+        let mut clients = self.clients.lock().unwrap();
+        clients.retain(|client| client.send(()).is_ok());
+        trace!("Sent reload event to {} pages", clients.len());
+    }
+
+    fn subscribe(&self) -> mpsc::Receiver<()> {
+        let (tx, rx) = mpsc::channel();
+        self.clients.lock().unwrap().push(tx);
+        rx
+    }
+}
+
+/// Serves the files in `root_dir` over HTTP on localhost until the process is stopped
+///
+/// When `live_reload` is given, pages can listen to its reload events on `LIVE_RELOAD_ROUTE`.
+pub fn serve(
+    root_dir: &Path,
+    port: u16,
+    target_ext: &str,
+    live_reload: Option<LiveReload>,
+) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .with_context(|| format!("Failed to bind the preview server to port {port}"))?;
+    info!("Serving site on http://localhost:{port}");
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Failed to accept connection: {e}");
+                continue;
+            }
+        };
+        let root_dir = root_dir.to_path_buf();
+        let target_ext = target_ext.to_string();
+        let live_reload = live_reload.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &root_dir, &target_ext, live_reload) {
+                warn!("Failed to handle request: {e:#}");
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    root_dir: &Path,
+    target_ext: &str,
+    live_reload: Option<LiveReload>,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the request headers, they are not needed to serve static files
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    trace!("{method} {target}");
+
+    if method != "GET" && method != "HEAD" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"",
+            false,
+        );
+    }
+    let url_path = target.split(['?', '#']).next().unwrap_or_default();
+    if let (LIVE_RELOAD_ROUTE, Some(live_reload)) = (url_path, live_reload) {
+        return stream_reload_events(&mut stream, &live_reload);
+    }
+    match resolve_path(root_dir, &percent_decode(url_path), target_ext) {
+        Some(path) => {
+            let body = fs::read(&path)?;
+            respond(
+                &mut stream,
+                "200 OK",
+                content_type(&path),
+                &body,
+                method == "HEAD",
+            )
+        }
+        None => respond(
+            &mut stream,
+            "404 Not Found",
+            "text/plain",
+            b"404 Not Found",
+            method == "HEAD",
+        ),
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+    head_only: bool,
+) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    if !head_only {
+        stream.write_all(body)?;
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+/// Keeps the connection open as an event stream and writes an event for every reload
+fn stream_reload_events(stream: &mut TcpStream, live_reload: &LiveReload) -> Result<()> {
+    let events = live_reload.subscribe();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n"
+    )?;
+    stream.flush()?;
+    loop {
+        // Writing fails once the page is closed, which ends the connection
+        match events.recv_timeout(KEEP_ALIVE_INTERVAL) {
+            Ok(()) => write!(stream, "data: reload\n\n")?,
+            Err(RecvTimeoutError::Timeout) => write!(stream, ": keep-alive\n\n")?,
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        stream.flush()?;
+    }
+}
+
+/// Maps an URL path to a file in the output directory
+///
+/// Extensionless routes (like `Location::short_route`) resolve to the file with the target
+/// extension before a directory with the same name, URLs ending in `/` and other directories
+/// resolve to their index file.
+fn resolve_path(root_dir: &Path, url_path: &str, target_ext: &str) -> Option<PathBuf> {
+    let mut path = root_dir.to_path_buf();
+    for component in Path::new(url_path.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(name) => path.push(name),
+            Component::CurDir => {}
+            // Never serve files outside of the root directory
+            _ => return None,
+        }
+    }
+
+    if !url_path.ends_with('/') {
+        if path.is_file() {
+            return Some(path);
+        }
+        let mut target_path = path.clone().into_os_string();
+        target_path.push(".");
+        target_path.push(target_ext);
+        let target_path = PathBuf::from(target_path);
+        if target_path.is_file() {
+            return Some(target_path);
+        }
+    }
+    let index_path = path.join(INDEX_TARGET_FS).with_extension(target_ext);
+    index_path.is_file().then_some(index_path)
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = input
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                output.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        output.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&output).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an output directory with the files at `paths` inside a new temporary directory
+    fn site(name: &str, paths: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("renatic-{}-{name}", std::process::id()));
+        let root_dir = dir.join("site");
+        let _ = fs::remove_dir_all(&dir);
+        for path in paths {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::create_dir_all(&root_dir).unwrap();
+        root_dir
+    }
+
+    fn resolve(root_dir: &Path, url_path: &str) -> Option<PathBuf> {
+        resolve_path(root_dir, &percent_decode(url_path), "html")
+            .map(|path| path.strip_prefix(root_dir).unwrap().to_path_buf())
+    }
+
+    #[test]
+    fn parent_dir_outside_root() {
+        let root_dir = site("parent", &["secret.html", "site/index.html"]);
+        assert_eq!(resolve(&root_dir, "/../secret.html"), None);
+        assert_eq!(resolve(&root_dir, "/a/../../secret"), None);
+    }
+
+    #[test]
+    fn percent_encoded_parent_dir() {
+        let root_dir = site("encoded", &["secret.html", "site/index.html"]);
+        assert_eq!(resolve(&root_dir, "/%2e%2e/secret.html"), None);
+        assert_eq!(resolve(&root_dir, "/%2E%2E%2Fsecret"), None);
+    }
+
+    #[test]
+    fn directory_index() {
+        let root_dir = site("index", &["site/index.html", "site/docs/index.html"]);
+        assert_eq!(resolve(&root_dir, "/"), Some(PathBuf::from("index.html")));
+        assert_eq!(
+            resolve(&root_dir, "/docs/"),
+            Some(PathBuf::from("docs/index.html"))
+        );
+        assert_eq!(
+            resolve(&root_dir, "/docs"),
+            Some(PathBuf::from("docs/index.html"))
+        );
+        assert_eq!(resolve(&root_dir, "/missing/"), None);
+    }
+
+    #[test]
+    fn short_route() {
+        let root_dir = site(
+            "short",
+            &["site/docs.html", "site/docs/index.html", "site/a b.css"],
+        );
+        assert_eq!(
+            resolve(&root_dir, "/docs"),
+            Some(PathBuf::from("docs.html"))
+        );
+        assert_eq!(
+            resolve(&root_dir, "/docs.html"),
+            Some(PathBuf::from("docs.html"))
+        );
+        assert_eq!(
+            resolve(&root_dir, "/docs/"),
+            Some(PathBuf::from("docs/index.html"))
+        );
+        assert_eq!(
+            resolve(&root_dir, "/a%20b.css"),
+            Some(PathBuf::from("a b.css"))
+        );
+    }
+}
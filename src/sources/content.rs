@@ -1,8 +1,22 @@
-use super::{meta::Meta, TemplateSource};
-use crate::{config::CollectionConfig, util::parser};
+use super::{
+    meta::{KeyError, Meta},
+    taxonomy::Taxonomy,
+    TemplateSource,
+};
+use crate::{
+    config::{CollectionConfig, Config},
+    consts::PAGE_DIR,
+    diagnostic::{error_message, Diagnostic},
+    util::{git::GitDates, parser, slug::title_from_slug},
+};
 use anyhow::{Context, Result};
 use serde::Serialize;
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use tera::Context as TemplateContext;
 
 impl TemplateSource for Entry {
@@ -22,23 +36,122 @@ pub struct Entry {
     pub meta: Meta,
     pub location: Location,
     pub content: String,
+    /// Content up to a `<!-- more -->` marker
+    #[serde(skip)]
+    excerpt: Option<String>,
     pub collection: Option<CollectionBinding>,
 }
 
 impl Entry {
-    pub fn load(location: Location, collection: Option<CollectionBinding>) -> Result<Entry> {
-        let file_str = fs::read_to_string(&location.source_path)?;
-        let (meta_str, source) = parser::parse_markdown_with_meta(&file_str)
+    pub fn load(
+        location: Location,
+        collection: Option<CollectionBinding>,
+        config: &Config,
+        git_dates: &GitDates,
+    ) -> Result<Entry> {
+        let file_str = fs::read_to_string(&location.source_path)
             .with_context(|| format!("Failed to read file '{}'", location.source_path.display()))?;
-        let meta = Meta::from_str(&meta_str).with_context(|| "Failed to parse content meta")?;
+        // Errors point to their position in the file
+        let diagnostic = |error: anyhow::Error, position: Option<(usize, usize)>| {
+            Diagnostic::new(
+                error_message(&error, position.is_some()),
+                &location.source_child_path,
+                &file_str,
+                position,
+            )
+        };
+        let (front_matter, markdown) = parser::split_front_matter(&file_str).map_err(|e| {
+            let position = parser::error_position(&e, 1).unwrap_or((1, 1));
+            diagnostic(e, Some(position))
+        })?;
+        let mapping = match &front_matter {
+            Some(front_matter) => front_matter.to_mapping().map_err(|e| {
+                let position = front_matter.error_position(&e);
+                diagnostic(e, position)
+            })?,
+            None => Default::default(),
+        };
+        // Files without title, like plain Markdown notes, are named by their heading or file
+        let default_title = || {
+            parser::first_heading(markdown).unwrap_or_else(|| {
+                let file_stem = location.source_path.file_stem().unwrap_or_default();
+                title_from_slug(&file_stem.to_string_lossy())
+            })
+        };
+        let mut meta = Meta::from_mapping(mapping, default_title, config).map_err(|e| {
+            let position = e
+                .downcast_ref::<KeyError>()
+                .zip(front_matter.as_ref())
+                .and_then(|(KeyError(key), front_matter)| front_matter.key_position(key));
+            diagnostic(e, position)
+        })?;
+        if meta.updated.is_none() {
+            meta.updated = git_dates.get(&location.source_path);
+        }
 
         Ok(Entry {
             location,
             meta,
-            content: source,
+            content: parser::markdown_to_html(markdown),
+            excerpt: parser::markdown_excerpt(markdown),
             collection,
         })
     }
+
+    /// Content up to a `<!-- more -->` marker, or the first paragraph without marker
+    pub fn excerpt(&self) -> &str {
+        self.excerpt
+            .as_deref()
+            .unwrap_or_else(|| parser::first_paragraph(&self.content))
+    }
+
+    /// Splits an entry with a collection binding into pages with `page_size` collection entries
+    ///
+    /// The first page keeps the location of the entry, the other pages are placed in
+    /// `page/<number>/` next to it.
+    pub fn paginate(self, page_size: usize, target_dir: &Path) -> Result<Vec<Entry>> {
+        let binding = self
+            .collection
+            .as_ref()
+            .context("Only entries with a collection can be paginated")?;
+        let chunks: Vec<&[Entry]> = binding.entries.chunks(page_size.max(1)).collect();
+        let total = chunks.len().max(1);
+        let locations = (1..=total)
+            .map(|page| self.location.page(page, target_dir))
+            .collect::<Result<Vec<Location>>>()?;
+        let routes: Vec<String> = locations
+            .iter()
+            .map(|location| location.directory_route())
+            .collect();
+
+        let mut pages = Vec::new();
+        for (index, location) in locations.into_iter().enumerate() {
+            let paginator = Paginator {
+                current: index + 1,
+                total,
+                previous: index
+                    .checked_sub(1)
+                    .map(|previous| routes[previous].clone()),
+                next: routes.get(index + 1).cloned(),
+                pages: routes.clone(),
+            };
+            pages.push(Entry {
+                meta: self.meta.clone(),
+                location,
+                content: self.content.clone(),
+                excerpt: self.excerpt.clone(),
+                collection: Some(CollectionBinding {
+                    entries: chunks.get(index).map(|c| c.to_vec()).unwrap_or_default(),
+                    taxonomies: binding.taxonomies.clone(),
+                    rss: binding.rss.clone(),
+                    atom: binding.atom.clone(),
+                    json_feed: binding.json_feed.clone(),
+                    paginator: Some(paginator),
+                }),
+            });
+        }
+        Ok(pages)
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -116,25 +229,79 @@ impl Location {
             short_route,
         })
     }
+
+    /// Location of a page of a paginated entry, the first page is the location itself
+    pub fn page(&self, page: usize, target_dir: &Path) -> Result<Self> {
+        if page <= 1 {
+            return Ok(self.clone());
+        }
+        let target_path = self
+            .target_path
+            .parent()
+            .unwrap_or(target_dir)
+            .join(PAGE_DIR)
+            .join(page.to_string())
+            .join(self.target_path.file_name().unwrap_or_default());
+        let target_child_path = target_path.strip_prefix(target_dir)?.to_path_buf();
+        let route = PathBuf::from("/").join(&target_child_path);
+        let short_route = route.with_extension("");
+        Ok(Self {
+            target_path,
+            target_child_path,
+            route,
+            short_route,
+            ..self.clone()
+        })
+    }
+
+    /// Route of the directory containing the target, like `/posts/`
+    pub fn directory_route(&self) -> String {
+        match self.route.parent() {
+            Some(parent) if parent != Path::new("/") => format!("{}/", parent.display()),
+            _ => String::from("/"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct CollectionBinding {
     pub entries: Vec<Entry>,
-    pub rss: Option<RssInfo>,
+    /// Terms of the entries by taxonomy name, like `tags`
+    pub taxonomies: BTreeMap<String, Arc<Taxonomy>>,
+    pub rss: Option<FeedInfo>,
+    pub atom: Option<FeedInfo>,
+    pub json_feed: Option<FeedInfo>,
+    pub paginator: Option<Paginator>,
 }
 
+/// Position of a page in a paginated collection index, pages are referred to by their route
 #[derive(Debug, Clone, Serialize)]
-pub struct RssInfo {
+pub struct Paginator {
+    /// Number of the current page, starting at 1
+    pub current: usize,
+    /// Total number of pages
+    pub total: usize,
+    pub previous: Option<String>,
+    pub next: Option<String>,
+    /// Routes of all pages in order
+    pub pages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedInfo {
     pub path: PathBuf,
     pub route: PathBuf,
 }
 
 impl CollectionBinding {
-    pub fn new(entries: Vec<Entry>, config: &CollectionConfig) -> Self {
+    pub fn new(
+        entries: Vec<Entry>,
+        taxonomies: BTreeMap<String, Arc<Taxonomy>>,
+        config: &CollectionConfig,
+    ) -> Self {
         let rss = {
             if let Some(rss_path) = &config.rss {
-                Some(RssInfo {
+                Some(FeedInfo {
                     path: rss_path.clone(),
                     route: PathBuf::from("/").join(&rss_path),
                 })
@@ -142,6 +309,141 @@ impl CollectionBinding {
                 None
             }
         };
-        Self { entries, rss }
+        let atom = config.atom.as_ref().map(|atom_path| FeedInfo {
+            path: atom_path.clone(),
+            route: PathBuf::from("/").join(atom_path),
+        });
+        let json_feed = config.json_feed.as_ref().map(|json_feed_path| FeedInfo {
+            path: json_feed_path.clone(),
+            route: PathBuf::from("/").join(json_feed_path),
+        });
+        Self {
+            entries,
+            taxonomies,
+            rss,
+            atom,
+            json_feed,
+            paginator: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_yaml::Mapping;
+
+    fn entry(child_path: &str, file_stem: Option<&str>) -> Entry {
+        let source_dir = PathBuf::from("/site");
+        let location = Location::new(
+            &source_dir.join(child_path),
+            &source_dir,
+            &PathBuf::from("/out"),
+            "html",
+            file_stem,
+        )
+        .unwrap();
+        let meta = Meta::from_mapping(
+            Mapping::new(),
+            || child_path.to_string(),
+            &Config::default(),
+        );
+        Entry {
+            meta: meta.unwrap(),
+            location,
+            content: String::new(),
+            excerpt: None,
+            collection: None,
+        }
+    }
+
+    /// Index of a collection with `count` entries
+    fn index(count: usize) -> Entry {
+        let entries = (1..=count)
+            .map(|number| entry(&format!("posts/{number}.md"), None))
+            .collect();
+        let binding =
+            CollectionBinding::new(entries, BTreeMap::new(), &CollectionConfig::default());
+        Entry {
+            collection: Some(binding),
+            ..entry("posts/_index.md", Some("index"))
+        }
+    }
+
+    fn titles(page: &Entry) -> Vec<&str> {
+        let binding = page.collection.as_ref().unwrap();
+        binding
+            .entries
+            .iter()
+            .map(|entry| entry.meta.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn pages_of_entries() {
+        let pages = index(5).paginate(2, Path::new("/out")).unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(titles(&pages[0]), ["posts/1.md", "posts/2.md"]);
+        assert_eq!(titles(&pages[2]), ["posts/5.md"]);
+
+        let routes = ["/posts/", "/posts/page/2/", "/posts/page/3/"];
+        assert_eq!(
+            pages[0].location.target_path,
+            Path::new("/out/posts/index.html")
+        );
+        assert_eq!(
+            pages[1].location.target_path,
+            Path::new("/out/posts/page/2/index.html")
+        );
+        assert_eq!(
+            pages[2].location.route,
+            Path::new("/posts/page/3/index.html")
+        );
+        for (index, page) in pages.iter().enumerate() {
+            let paginator = page
+                .collection
+                .as_ref()
+                .unwrap()
+                .paginator
+                .as_ref()
+                .unwrap();
+            assert_eq!(paginator.current, index + 1);
+            assert_eq!(paginator.total, 3);
+            assert_eq!(paginator.pages, routes);
+            assert_eq!(
+                paginator.previous.as_deref(),
+                index.checked_sub(1).map(|i| routes[i])
+            );
+            assert_eq!(paginator.next.as_deref(), routes.get(index + 1).copied());
+        }
+    }
+
+    #[test]
+    fn empty_collection_has_one_page() {
+        let pages = index(0).paginate(10, Path::new("/out")).unwrap();
+        assert_eq!(pages.len(), 1);
+        assert!(titles(&pages[0]).is_empty());
+        let paginator = pages[0]
+            .collection
+            .as_ref()
+            .unwrap()
+            .paginator
+            .as_ref()
+            .unwrap();
+        assert_eq!((paginator.current, paginator.total), (1, 1));
+        assert_eq!((&paginator.previous, &paginator.next), (&None, &None));
+    }
+
+    #[test]
+    fn page_size_of_zero() {
+        let pages = index(2).paginate(0, Path::new("/out")).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(titles(&pages[1]), ["posts/2.md"]);
+    }
+
+    #[test]
+    fn entry_without_collection() {
+        let entry = entry("about.md", None);
+        assert!(entry.paginate(10, Path::new("/out")).is_err());
     }
 }
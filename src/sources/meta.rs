@@ -1,98 +1,212 @@
-use std::path::PathBuf;
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+};
 
-use anyhow::{Context, Result};
-use chrono::NaiveDate;
-use serde::{Serialize, Serializer};
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone};
+use chrono_tz::Tz;
+use serde::{de::DeserializeOwned, Serialize, Serializer};
 use serde_yaml::{Mapping, Value};
 
-const DATE_FORMAT: &'static str = "%Y-%m-%d";
+/// Format of the dates of new content
+pub const DATE_FORMAT: &'static str = "%Y-%m-%d";
 
 #[derive(Debug, Clone)]
 pub struct Meta {
     pub title: String,
-    pub date: Option<NaiveDate>,
+    pub date: Option<DateTime<FixedOffset>>,
+    /// Date of the last significant change after publication
+    pub updated: Option<DateTime<FixedOffset>>,
+    /// Drafts are only generated when explicitly enabled
+    pub draft: bool,
+    /// Date from which the content is not published anymore
+    pub expires: Option<DateTime<FixedOffset>>,
     pub category: Option<String>,
     pub tags: Option<Vec<String>>,
     pub template: Option<PathBuf>,
+    /// Keys of the dates without time, which are passed to templates without time as well
+    date_only: Vec<&'static str>,
     custom_fields: Mapping,
 }
 
-fn get_str_value(key: &str, map: &Mapping) -> Result<String> {
-    let value = map
-        .get(&Value::String(key.to_string()))
-        .with_context(|| format!("Failed to get required key '{key}'"))?;
-    let str = value.as_str().context(format!(
-        "Failed to get required value string for key '{key}'"
-    ))?;
+/// Error in the value of a meta key, the key is used to point to the value in the front matter
+#[derive(Debug)]
+pub struct KeyError(pub String);
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid value of '{}'", self.0)
+    }
+}
+
+/// Reads the value of `key` if it is set, errors are marked with a `KeyError`
+fn read_key<T>(
+    key: &str,
+    map: &Mapping,
+    read: impl FnOnce(&Value) -> Result<T>,
+) -> Result<Option<T>> {
+    map.get(&Value::String(key.to_string()))
+        .map(read)
+        .transpose()
+        .with_context(|| KeyError(key.to_string()))
+}
+
+fn read_str(value: &Value) -> Result<String> {
+    let str = value.as_str().context("Expected a string")?;
     Ok(str.to_string())
 }
 
+fn get_str_value(key: &str, map: &Mapping) -> Result<Option<String>> {
+    read_key(key, map, read_str)
+}
+
+fn get_date_value(
+    key: &str,
+    map: &Mapping,
+    config: &Config,
+) -> Result<Option<(DateTime<FixedOffset>, bool)>> {
+    read_key(key, map, |value| {
+        let date_str = value.as_str().context("Expected a date string")?;
+        parse_date(date_str, &config.date_formats, config.timezone)
+    })
+}
+
+/// Parses an RFC 3339 datetime like `2022-02-22T14:00:00+01:00` or a date in one of `formats`.
+/// Dates in [`DATE_FORMAT`], which new entries use, are always accepted.
+///
+/// Dates without offset are in `timezone` and dates without time are at the start of the day.
+/// Also returns whether the input has a time.
+fn parse_date(
+    input: &str,
+    formats: &[String],
+    timezone: Tz,
+) -> Result<(DateTime<FixedOffset>, bool)> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(input) {
+        return Ok((date_time, true));
+    }
+    let mut formats: Vec<&str> = formats.iter().map(String::as_str).collect();
+    if !formats.contains(&DATE_FORMAT) {
+        formats.push(DATE_FORMAT);
+    }
+    for format in formats.iter() {
+        if let Ok(date_time) = DateTime::parse_from_str(input, format) {
+            return Ok((date_time, true));
+        }
+        let naive = NaiveDateTime::parse_from_str(input, format)
+            .map(|naive| (naive, true))
+            .or_else(|_| {
+                NaiveDate::parse_from_str(input, format)
+                    .map(|date| (date.and_hms_opt(0, 0, 0).unwrap_or_default(), false))
+            });
+        if let Ok((naive, has_time)) = naive {
+            let date_time = timezone
+                .from_local_datetime(&naive)
+                .earliest()
+                .with_context(|| {
+                    format!("The date '{input}' doesn't exist in timezone {timezone}")
+                })?;
+            return Ok((date_time.with_timezone(&date_time.offset().fix()), has_time));
+        }
+    }
+    bail!(
+        "Invalid date '{input}', expected RFC 3339 or one of the formats {}",
+        formats.join(", ")
+    )
+}
+
 impl Meta {
-    pub fn from_str(input: &str) -> Result<Self> {
-        let mut meta = serde_yaml::from_str::<Mapping>(input)
-            .with_context(|| format!("Failed to read YAML input: '{input}'"))?;
-        let title = get_str_value("title", &meta)?;
-        let date = {
-            if let Some(value) = meta.get(&Value::String("date".to_string())) {
-                let date_str = value.as_str().context("Failed to read date as a string.")?;
-                Some(NaiveDate::parse_from_str(&date_str, DATE_FORMAT)?)
-            } else {
-                None
-            }
-        };
-        let category = {
-            if let Some(value) = meta.get(&Value::String("category".to_string())) {
-                let category_str = value
-                    .as_str()
-                    .context("Failed read category as a string.")?;
-                Some(category_str.to_string())
-            } else {
-                None
-            }
-        };
-        let tags = {
-            if let Some(value) = meta.get(&Value::String("tags".to_string())) {
-                let tags_squence = value
-                    .as_sequence()
-                    .context("Failed to read tags as a sequence")?;
-                let mut tags = Vec::new();
-                for tag_val in tags_squence {
-                    tags.push(
-                        tag_val
-                            .as_str()
-                            .with_context(|| "Failed to convert tag value to string.")?
-                            .to_string(),
-                    );
-                }
-                Some(tags)
-            } else {
-                None
-            }
-        };
-        let template = {
-            if let Some(value) = meta.get(&Value::String("template".to_string())) {
-                let template_str = value
-                    .as_str()
-                    .context("Failed to read template as a string.")?;
-                Some(PathBuf::from(template_str))
-            } else {
-                None
+    /// Reads the meta from the front matter with the date formats and timezone of `config`,
+    /// `default_title` gives the title when the front matter doesn't have one
+    pub fn from_mapping(
+        mut meta: Mapping,
+        default_title: impl FnOnce() -> String,
+        config: &Config,
+    ) -> Result<Self> {
+        let title = get_str_value("title", &meta)?.unwrap_or_else(default_title);
+        let mut date_only = Vec::new();
+        let mut read_date = |key: &'static str| -> Result<Option<DateTime<FixedOffset>>> {
+            let value = get_date_value(key, &meta, config)?;
+            if let Some((_, false)) = value {
+                date_only.push(key);
             }
+            Ok(value.map(|(date, _)| date))
         };
+        let date = read_date("date")?;
+        let updated = read_date("updated")?;
+        let draft = read_key("draft", &meta, |value| {
+            value.as_bool().context("Expected true or false")
+        })?
+        .unwrap_or(false);
+        let expires = read_date("expires")?;
+        let category = get_str_value("category", &meta)?;
+        let tags = read_key("tags", &meta, |value| {
+            value
+                .as_sequence()
+                .context("Expected a list of tags")?
+                .iter()
+                .map(read_str)
+                .collect::<Result<Vec<String>>>()
+        })?;
+        let template = get_str_value("template", &meta)?.map(PathBuf::from);
 
-        for key in vec!["title", "date", "category", "tags"] {
+        for key in vec![
+            "title", "date", "updated", "draft", "expires", "category", "tags",
+        ] {
             meta.remove(&Value::String(key.to_string()));
         }
 
         Ok(Self {
             title,
             date,
+            updated,
+            draft,
+            expires,
             category,
             tags,
             template,
+            date_only,
             custom_fields: meta,
         })
     }
+
+    /// Value of a custom field that is a string
+    pub fn custom_str(&self, key: &str) -> Option<&str> {
+        self.custom_fields
+            .get(&Value::String(key.to_string()))
+            .and_then(|value| value.as_str())
+    }
+
+    /// Value of a custom field deserialized into `T`
+    pub fn custom_field<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.custom_fields
+            .get(&Value::String(key.to_string()))
+            .map(|value| serde_yaml::from_value(value.clone()))
+            .transpose()
+            .with_context(|| format!("Failed to read custom field '{key}'"))
+    }
+
+    /// The date as it is passed to templates
+    pub fn date_str(&self) -> Option<String> {
+        self.date.as_ref().map(|date| self.date_value("date", date))
+    }
+
+    /// Date as it is passed to templates, without time if the front matter has none
+    fn date_value(&self, key: &str, date: &DateTime<FixedOffset>) -> String {
+        if self.date_only.contains(&key) {
+            date.format(DATE_FORMAT).to_string()
+        } else {
+            date.to_rfc3339()
+        }
+    }
+
+    /// Whether the content is published at the given time
+    pub fn is_published(&self, now: DateTime<FixedOffset>, drafts: bool, future: bool) -> bool {
+        let is_future = self.date.is_some_and(|date| date > now);
+        let is_expired = self.expires.is_some_and(|expires| expires <= now);
+        (drafts || !self.draft) && (future || !is_future) && !is_expired
+    }
 }
 
 impl Serialize for Meta {
@@ -108,7 +222,22 @@ impl Serialize for Meta {
         if let Some(date) = &self.date {
             values.insert(
                 Value::String("date".to_string()),
-                Value::String(date.format(DATE_FORMAT).to_string()),
+                Value::String(self.date_value("date", date)),
+            );
+        };
+        if let Some(updated) = &self.updated {
+            values.insert(
+                Value::String("updated".to_string()),
+                Value::String(self.date_value("updated", updated)),
+            );
+        };
+        if self.draft {
+            values.insert(Value::String("draft".to_string()), Value::Bool(true));
+        }
+        if let Some(expires) = &self.expires {
+            values.insert(
+                Value::String("expires".to_string()),
+                Value::String(self.date_value("expires", expires)),
             );
         };
         if let Some(category) = &self.category {
@@ -126,3 +255,61 @@ impl Serialize for Meta {
         values.serialize(serializer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formats(formats: &[&str]) -> Vec<String> {
+        formats.iter().map(|format| format.to_string()).collect()
+    }
+
+    fn parse(input: &str, formats: &[String]) -> Result<(String, bool)> {
+        let (date, has_time) = parse_date(input, formats, Tz::Europe__Amsterdam)?;
+        Ok((date.to_rfc3339(), has_time))
+    }
+
+    #[test]
+    fn rfc3339_keeps_offset() {
+        let (date, has_time) = parse("2024-07-01T14:00:00-05:00", &[]).unwrap();
+        assert_eq!(date, "2024-07-01T14:00:00-05:00");
+        assert!(has_time);
+    }
+
+    #[test]
+    fn date_without_time_in_timezone() {
+        let (winter, has_time) = parse("2024-01-15", &[]).unwrap();
+        assert_eq!(winter, "2024-01-15T00:00:00+01:00");
+        assert!(!has_time);
+        let (summer, _) = parse("2024-07-01", &[]).unwrap();
+        assert_eq!(summer, "2024-07-01T00:00:00+02:00");
+    }
+
+    #[test]
+    fn configured_formats() {
+        let formats = formats(&["%d-%m-%Y", "%d-%m-%Y %H:%M"]);
+        assert_eq!(
+            parse("15-01-2024", &formats).unwrap(),
+            ("2024-01-15T00:00:00+01:00".to_string(), false)
+        );
+        assert_eq!(
+            parse("15-01-2024 09:30", &formats).unwrap(),
+            ("2024-01-15T09:30:00+01:00".to_string(), true)
+        );
+        // New entries are written in the default format, which is always accepted
+        assert_eq!(
+            parse("2024-01-15", &formats).unwrap(),
+            ("2024-01-15T00:00:00+01:00".to_string(), false)
+        );
+        assert!(parse("2024-01-15 09:30:00", &formats).is_err());
+    }
+
+    #[test]
+    fn invalid_dates() {
+        let formats = formats(&[DATE_FORMAT, "%Y-%m-%d %H:%M"]);
+        assert!(parse("2024-02-30", &formats).is_err());
+        assert!(parse("yesterday", &formats).is_err());
+        // Skipped by the switch to daylight saving time
+        assert!(parse("2024-03-31 02:30", &formats).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod meta;
 pub mod content;
+pub mod taxonomy;
 
 use tera::Context as TemplateContext;
 
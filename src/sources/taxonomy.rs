@@ -0,0 +1,149 @@
+use super::{content::Entry, meta::Meta, TemplateSource};
+use crate::{consts::TERM_RSS_FN, util::slug::slugify};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tera::Context as TemplateContext;
+
+impl TemplateSource for TaxonomyPage {
+    fn get_context(&self) -> TemplateContext {
+        let mut context = TemplateContext::new();
+        match self
+            .term
+            .as_ref()
+            .and_then(|slug| self.taxonomy.terms.get(slug))
+        {
+            // A term page only gets its own term, not the entries of every other term
+            Some(term) => {
+                context.insert(
+                    "taxonomy",
+                    &TaxonomyInfo {
+                        name: &self.taxonomy.name,
+                        route: &self.taxonomy.route,
+                    },
+                );
+                context.insert("term", term);
+            }
+            None => context.insert("taxonomy", self.taxonomy.as_ref()),
+        }
+        context
+    }
+}
+
+/// Ways of grouping the entries of a collection by their meta
+#[derive(Debug, Clone, Copy)]
+pub enum TaxonomyKind {
+    Tags,
+    Categories,
+}
+
+impl TaxonomyKind {
+    pub const ALL: [TaxonomyKind; 2] = [TaxonomyKind::Tags, TaxonomyKind::Categories];
+
+    /// Name of the taxonomy, also used as the directory of its pages
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaxonomyKind::Tags => "tags",
+            TaxonomyKind::Categories => "categories",
+        }
+    }
+
+    fn terms<'a>(&self, meta: &'a Meta) -> Vec<&'a String> {
+        match self {
+            TaxonomyKind::Tags => meta.tags.iter().flatten().collect(),
+            TaxonomyKind::Categories => meta.category.iter().collect(),
+        }
+    }
+}
+
+/// All terms of a taxonomy in a collection with their entries
+#[derive(Debug, Clone, Serialize)]
+pub struct Taxonomy {
+    pub name: String,
+    pub route: String,
+    /// Terms by their slug
+    pub terms: BTreeMap<String, Term>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Term {
+    /// Name of the term as it was first used
+    pub name: String,
+    pub slug: String,
+    pub route: String,
+    /// Route of the RSS feed of the term if enabled
+    pub rss: Option<String>,
+    pub entries: Vec<TermEntry>,
+}
+
+/// Entry with a term, with only what is needed to list and link it
+#[derive(Debug, Clone, Serialize)]
+pub struct TermEntry {
+    pub title: String,
+    pub route: PathBuf,
+    /// Date like `meta.date` of the entry
+    pub date: Option<String>,
+    /// Position of the entry in the entries of the collection
+    #[serde(skip)]
+    pub index: usize,
+}
+
+impl Taxonomy {
+    /// Groups entries by their terms, `child_dir` is the output directory of the taxonomy
+    /// relative to the output directory and `rss` whether the terms have a feed
+    ///
+    /// Terms with the same slug, like `Rust` and `rust`, are merged.
+    pub fn new(kind: TaxonomyKind, child_dir: &Path, entries: &[Entry], rss: bool) -> Self {
+        let route = format!("/{}/", child_dir.display());
+        let mut terms: BTreeMap<String, Term> = BTreeMap::new();
+        for (index, entry) in entries.iter().enumerate() {
+            for name in kind.terms(&entry.meta) {
+                let slug = slugify(name);
+                if slug.is_empty() {
+                    continue;
+                }
+                let term = terms.entry(slug.clone()).or_insert_with(|| Term {
+                    name: name.clone(),
+                    route: format!("{route}{slug}/"),
+                    rss: rss.then(|| format!("{route}{slug}/{TERM_RSS_FN}")),
+                    slug,
+                    entries: Vec::new(),
+                });
+                // The same term can be listed twice in the tags of an entry
+                if term.entries.last().is_none_or(|last| last.index != index) {
+                    term.entries.push(TermEntry {
+                        title: entry.meta.title.clone(),
+                        route: entry.location.short_route.clone(),
+                        date: entry.meta.date_str(),
+                        index,
+                    });
+                }
+            }
+        }
+        Self {
+            name: kind.name().to_string(),
+            route,
+            terms,
+        }
+    }
+}
+
+/// Page of a taxonomy without source file, either listing all terms or the entries of one term
+///
+/// All pages of a taxonomy share it.
+#[derive(Debug, Clone)]
+pub struct TaxonomyPage {
+    pub taxonomy: Arc<Taxonomy>,
+    /// Slug of the term of a term page, `None` for the page listing all terms
+    pub term: Option<String>,
+}
+
+/// The taxonomy of a term page, without the terms
+#[derive(Serialize)]
+struct TaxonomyInfo<'a> {
+    name: &'a str,
+    route: &'a str,
+}
@@ -0,0 +1,135 @@
+use super::date;
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, Utc};
+use quick_xml::{se::Serializer, Writer};
+use serde::Serialize;
+
+#[derive(Clone, Serialize)]
+pub struct AtomFeed {
+    pub xmlns: String,
+    #[serde(rename = "$unflatten=title")]
+    pub title: String,
+    #[serde(rename = "$unflatten=subtitle")]
+    pub subtitle: String,
+    #[serde(rename = "$unflatten=id")]
+    pub id: String,
+    #[serde(rename = "$unflatten=updated")]
+    #[serde(serialize_with = "date::serialize_rfc3339")]
+    pub updated: DateTime<FixedOffset>,
+    #[serde(rename = "link")]
+    pub links: Vec<AtomLink>,
+    pub author: AtomPerson,
+    #[serde(rename = "$unflatten=generator")]
+    pub generator: String,
+    #[serde(rename = "entry")]
+    pub entries: Vec<AtomEntry>,
+}
+
+impl AtomFeed {
+    /// Creates a feed with `link` as the location of the feed itself and `alternate` as the
+    /// page it belongs to, the feed is updated with its latest entry
+    pub fn new(
+        title: String,
+        subtitle: String,
+        link: String,
+        alternate: String,
+        author: String,
+        entries: Vec<AtomEntry>,
+    ) -> Self {
+        let updated = entries
+            .iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or_else(|| Utc::now().into());
+        Self {
+            xmlns: "http://www.w3.org/2005/Atom".to_string(),
+            title,
+            subtitle,
+            id: link.clone(),
+            updated,
+            links: vec![
+                AtomLink::new(link, "self"),
+                AtomLink::new(alternate, "alternate"),
+            ],
+            author: AtomPerson { name: author },
+            generator: "renatic".to_string(),
+            entries,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct AtomEntry {
+    #[serde(rename = "$unflatten=title")]
+    pub title: String,
+    #[serde(rename = "$unflatten=id")]
+    pub id: String,
+    #[serde(rename = "$unflatten=updated")]
+    #[serde(serialize_with = "date::serialize_rfc3339")]
+    pub updated: DateTime<FixedOffset>,
+    /// Left out for entries without a date
+    #[serde(rename = "$unflatten=published")]
+    #[serde(serialize_with = "date::serialize_rfc3339_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<DateTime<FixedOffset>>,
+    pub link: AtomLink,
+    #[serde(rename = "category")]
+    pub categories: Vec<AtomCategory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<AtomContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<AtomContent>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AtomLink {
+    pub href: String,
+    pub rel: String,
+}
+
+impl AtomLink {
+    pub fn new(href: String, rel: &str) -> Self {
+        Self {
+            href,
+            rel: rel.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct AtomPerson {
+    #[serde(rename = "$unflatten=name")]
+    pub name: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AtomCategory {
+    pub term: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct AtomContent {
+    #[serde(rename = "type")]
+    pub content_type: String,
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+impl AtomContent {
+    pub fn html(value: String) -> Self {
+        Self {
+            content_type: "html".to_string(),
+            value,
+        }
+    }
+}
+
+pub fn to_str(feed: AtomFeed) -> Result<String> {
+    let mut buffer = Vec::new();
+    let writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+    let mut ser = Serializer::with_root(writer, Some("feed"));
+
+    feed.serialize(&mut ser)?;
+    let string = String::from_utf8(buffer)?;
+    Ok(string)
+}
@@ -0,0 +1,379 @@
+use anyhow::{bail, Result};
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize, Serializer};
+use std::{collections::HashMap, str::FromStr};
+use tera::{Error as TemplateError, Filter, Value};
+
+/// Language of the month and weekday names of formatted dates
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Nl,
+    De,
+    Fr,
+    Es,
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    /// Parses a language code, optionally with a region like `nl_NL` or `nl-BE`
+    fn from_str(input: &str) -> Result<Self> {
+        let language = input.split(['_', '-']).next().unwrap_or_default();
+        Ok(match language.to_lowercase().as_str() {
+            "en" => Locale::En,
+            "nl" => Locale::Nl,
+            "de" => Locale::De,
+            "fr" => Locale::Fr,
+            "es" => Locale::Es,
+            _ => bail!("Unsupported locale '{input}', expected one of en, nl, de, fr or es"),
+        })
+    }
+}
+
+impl Locale {
+    fn months(&self) -> [&'static str; 12] {
+        match self {
+            Locale::En => [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ],
+            Locale::Nl => [
+                "januari",
+                "februari",
+                "maart",
+                "april",
+                "mei",
+                "juni",
+                "juli",
+                "augustus",
+                "september",
+                "oktober",
+                "november",
+                "december",
+            ],
+            Locale::De => [
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ],
+            Locale::Fr => [
+                "janvier",
+                "février",
+                "mars",
+                "avril",
+                "mai",
+                "juin",
+                "juillet",
+                "août",
+                "septembre",
+                "octobre",
+                "novembre",
+                "décembre",
+            ],
+            Locale::Es => [
+                "enero",
+                "febrero",
+                "marzo",
+                "abril",
+                "mayo",
+                "junio",
+                "julio",
+                "agosto",
+                "septiembre",
+                "octubre",
+                "noviembre",
+                "diciembre",
+            ],
+        }
+    }
+
+    fn short_months(&self) -> [&'static str; 12] {
+        match self {
+            Locale::En => [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ],
+            Locale::Nl => [
+                "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+            ],
+            Locale::De => [
+                "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+            ],
+            Locale::Fr => [
+                "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.",
+                "nov.", "déc.",
+            ],
+            Locale::Es => [
+                "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+            ],
+        }
+    }
+
+    /// Names of the weekdays starting at Monday
+    fn weekdays(&self) -> [&'static str; 7] {
+        match self {
+            Locale::En => [
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ],
+            Locale::Nl => [
+                "maandag",
+                "dinsdag",
+                "woensdag",
+                "donderdag",
+                "vrijdag",
+                "zaterdag",
+                "zondag",
+            ],
+            Locale::De => [
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+                "Sonntag",
+            ],
+            Locale::Fr => [
+                "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+            ],
+            Locale::Es => [
+                "lunes",
+                "martes",
+                "miércoles",
+                "jueves",
+                "viernes",
+                "sábado",
+                "domingo",
+            ],
+        }
+    }
+
+    fn short_weekdays(&self) -> [&'static str; 7] {
+        match self {
+            Locale::En => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+            Locale::Nl => ["ma", "di", "wo", "do", "vr", "za", "zo"],
+            Locale::De => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+            Locale::Fr => ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+            Locale::Es => ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+        }
+    }
+}
+
+/// Formats a date like `chrono`, but with the month and weekday names (`%B`, `%b`, `%A` and
+/// `%a`) in the language of `locale`, fails on an invalid format
+pub fn format_date(date: &DateTime<FixedOffset>, format: &str, locale: Locale) -> Result<String> {
+    let month = date.month0() as usize;
+    let weekday = date.weekday().num_days_from_monday() as usize;
+    let mut localized = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            localized.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('B') => localized.push_str(locale.months()[month]),
+            Some('b') | Some('h') => localized.push_str(locale.short_months()[month]),
+            Some('A') => localized.push_str(locale.weekdays()[weekday]),
+            Some('a') => localized.push_str(locale.short_weekdays()[weekday]),
+            Some(next) => {
+                localized.push('%');
+                localized.push(next);
+            }
+            None => localized.push('%'),
+        }
+    }
+    // Formatting with an invalid item panics
+    if StrftimeItems::new(&localized).any(|item| matches!(item, Item::Error)) {
+        bail!("Invalid date format '{format}'");
+    }
+    Ok(date.format(&localized).to_string())
+}
+
+/// The `date` template filter, replacing the filter of Tera with one that uses the locale and
+/// timezone of the site
+///
+/// Accepts RFC 3339 datetimes like the dates in the meta, datetimes without offset and plain
+/// dates in the timezone of the site, and timestamps. The `format`, `locale` and `timezone`
+/// arguments override the defaults.
+pub struct DateFilter {
+    pub locale: Locale,
+    pub timezone: Tz,
+}
+
+impl Filter for DateFilter {
+    fn filter(&self, value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let format = match args.get("format") {
+            Some(format) => format
+                .as_str()
+                .ok_or_else(|| TemplateError::msg("The date format must be a string"))?,
+            None => "%Y-%m-%d",
+        };
+        let locale = match args.get("locale").map(Value::as_str) {
+            Some(Some(locale)) => {
+                Locale::from_str(locale).map_err(|e| TemplateError::msg(e.to_string()))?
+            }
+            Some(None) => return Err(TemplateError::msg("The date locale must be a string")),
+            None => self.locale,
+        };
+
+        let date = match value {
+            Value::String(input) => DateTime::parse_from_rfc3339(input)
+                .ok()
+                .or_else(|| {
+                    let naive = input.parse::<NaiveDateTime>().ok().or_else(|| {
+                        let date = NaiveDate::parse_from_str(input, "%Y-%m-%d").ok()?;
+                        date.and_hms_opt(0, 0, 0)
+                    })?;
+                    let date = self.timezone.from_local_datetime(&naive).earliest()?;
+                    Some(with_timezone(&date, self.timezone))
+                })
+                .ok_or_else(|| TemplateError::msg(format!("Invalid date '{input}'")))?,
+            Value::Number(timestamp) => {
+                let date = timestamp
+                    .as_i64()
+                    .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single())
+                    .ok_or_else(|| TemplateError::msg(format!("Invalid timestamp {timestamp}")))?;
+                with_timezone(&date, self.timezone)
+            }
+            _ => {
+                return Err(TemplateError::msg(format!(
+                    "Expected a date or timestamp, got '{value}'"
+                )))
+            }
+        };
+        let date = match args.get("timezone").map(Value::as_str) {
+            Some(Some(timezone)) => {
+                let timezone = Tz::from_str(timezone)
+                    .map_err(|_| TemplateError::msg(format!("Unknown timezone '{timezone}'")))?;
+                with_timezone(&date, timezone)
+            }
+            Some(None) => return Err(TemplateError::msg("The timezone must be a string")),
+            None => date,
+        };
+        let formatted =
+            format_date(&date, format, locale).map_err(|e| TemplateError::msg(e.to_string()))?;
+        Ok(Value::String(formatted))
+    }
+}
+
+/// The current date in `timezone`, which is the day that dates without time are published
+pub fn today(timezone: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&timezone).date_naive()
+}
+
+/// Serializes a date as an RFC 3339 string, for fields with `#[serde(serialize_with = ...)]`
+pub fn serialize_rfc3339<S>(date: &DateTime<FixedOffset>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&date.to_rfc3339())
+}
+
+/// Serializes an optional date as an RFC 3339 string, for fields that are skipped when unset
+pub fn serialize_rfc3339_option<S>(
+    date: &Option<DateTime<FixedOffset>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match date {
+        Some(date) => serialize_rfc3339(date, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn with_timezone<T: TimeZone>(date: &DateTime<T>, timezone: Tz) -> DateTime<FixedOffset> {
+    let date = date.with_timezone(&timezone);
+    date.with_timezone(&date.offset().fix())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(input: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(input).unwrap()
+    }
+
+    fn filter(value: Value, format: &str) -> tera::Result<Value> {
+        let filter = DateFilter {
+            locale: Locale::Nl,
+            timezone: Tz::Europe__Amsterdam,
+        };
+        let args = HashMap::from([("format".to_string(), Value::String(format.to_string()))]);
+        filter.filter(&value, &args)
+    }
+
+    #[test]
+    fn localized_names() {
+        let date = date("2024-03-04T10:30:00+01:00");
+        let format = "%A %-d %B, %a %b";
+        let formatted = |locale| format_date(&date, format, locale).unwrap();
+        assert_eq!(formatted(Locale::En), "Monday 4 March, Mon Mar");
+        assert_eq!(formatted(Locale::Nl), "maandag 4 maart, ma mrt");
+        assert_eq!(formatted(Locale::Fr), "lundi 4 mars, lun. mars");
+    }
+
+    #[test]
+    fn escaped_percent_sign() {
+        let date = date("2024-03-04T10:30:00+01:00");
+        assert_eq!(
+            format_date(&date, "100%% %B", Locale::Nl).unwrap(),
+            "100% maart"
+        );
+        assert_eq!(format_date(&date, "%%B", Locale::Nl).unwrap(), "%B");
+    }
+
+    #[test]
+    fn invalid_format() {
+        let date = date("2024-03-04T10:30:00+01:00");
+        assert!(format_date(&date, "%Q", Locale::En).is_err());
+        assert!(format_date(&date, "%Y-%m-%d %", Locale::En).is_err());
+    }
+
+    #[test]
+    fn filter_inputs_in_site_timezone() {
+        let formatted = |value| filter(value, "%Y-%m-%d %H:%M %z").unwrap();
+        let expected = Value::String("2024-07-01 00:00 +0200".to_string());
+        assert_eq!(formatted(Value::String("2024-07-01".to_string())), expected);
+        assert_eq!(
+            formatted(Value::String("2024-07-01T00:00:00".to_string())),
+            expected
+        );
+        assert_eq!(formatted(Value::from(1719784800)), expected);
+        assert!(filter(Value::String("01-07-2024".to_string()), "%Y").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, FixedOffset};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Marks the lines with the commit date in the output of `git log`
+const DATE_MARKER: char = '\0';
+
+/// Dates of the last commits that changed the files of a git repository
+///
+/// Only the history in the local repository is used, so in a shallow clone the files that didn't
+/// change in the fetched commits get the date of the oldest fetched commit.
+#[derive(Debug, Default)]
+pub struct GitDates {
+    /// Last commit date by absolute file path
+    dates: HashMap<PathBuf, DateTime<FixedOffset>>,
+}
+
+impl GitDates {
+    /// Reads the history of the files in `dir` with a single `git log`
+    pub fn load(dir: &Path) -> Result<Self> {
+        let root = git(dir, &["rev-parse", "--show-toplevel"])?;
+        let root = PathBuf::from(root.trim())
+            .canonicalize()
+            .context("Failed to find the root of the git repository")?;
+        let log = git(
+            dir,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "log",
+                "--format=%x00%cI",
+                "--name-only",
+                "--no-renames",
+                "--",
+                ".",
+            ],
+        )?;
+
+        let mut dates = HashMap::new();
+        let mut date = None;
+        for line in log.lines() {
+            if let Some(date_str) = line.strip_prefix(DATE_MARKER) {
+                date = Some(
+                    DateTime::parse_from_rfc3339(date_str)
+                        .with_context(|| format!("Invalid commit date '{date_str}'"))?,
+                );
+            } else if let Some(date) = date.filter(|_| !line.is_empty()) {
+                // The log starts with the latest commit, so the first date of a file is its last
+                dates.entry(root.join(line)).or_insert(date);
+            }
+        }
+        Ok(Self { dates })
+    }
+
+    /// Date of the last commit that changed the file at `path`, `None` for uncommitted files
+    pub fn get(&self, path: &Path) -> Option<DateTime<FixedOffset>> {
+        self.dates.get(path).copied()
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).context("Invalid output of git")
+}
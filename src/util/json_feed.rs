@@ -0,0 +1,63 @@
+use super::date;
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+/// Feed in the JSON Feed 1.1 format, see <https://jsonfeed.org/version/1.1>
+#[derive(Clone, Serialize)]
+pub struct JsonFeed {
+    pub version: String,
+    pub title: String,
+    pub home_page_url: String,
+    pub feed_url: String,
+    pub description: String,
+    pub authors: Vec<JsonFeedAuthor>,
+    pub items: Vec<JsonFeedItem>,
+}
+
+impl JsonFeed {
+    pub fn new(
+        title: String,
+        home_page_url: String,
+        feed_url: String,
+        description: String,
+        authors: Vec<JsonFeedAuthor>,
+        items: Vec<JsonFeedItem>,
+    ) -> Self {
+        Self {
+            version: "https://jsonfeed.org/version/1.1".to_string(),
+            title,
+            home_page_url,
+            feed_url,
+            description,
+            authors,
+            items,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct JsonFeedAuthor {
+    pub name: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct JsonFeedItem {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub content_html: String,
+    /// Left out for entries without a date
+    #[serde(serialize_with = "date::serialize_rfc3339_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_published: Option<DateTime<FixedOffset>>,
+    #[serde(serialize_with = "date::serialize_rfc3339_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_modified: Option<DateTime<FixedOffset>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+pub fn to_str(feed: JsonFeed) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&feed)?)
+}
@@ -1,7 +1,7 @@
 use clap::ArgEnum;
 use minify_html::{minify, Cfg};
 
-#[derive(Clone, ArgEnum)]
+#[derive(Clone, Hash, ArgEnum)]
 pub enum MinificationLevel {
     Disabled,
     SpecCompliant,
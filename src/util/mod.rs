@@ -1,3 +1,10 @@
+pub mod atom;
+pub mod date;
+pub mod git;
+pub mod json_feed;
+pub mod mime;
 pub mod minifier;
 pub mod parser;
 pub mod rss;
+pub mod sitemap;
+pub mod slug;
@@ -1,24 +1,366 @@
-use anyhow::{anyhow, Result};
-use pulldown_cmark::{html, Options, Parser};
+use anyhow::{bail, Context, Result};
+use pulldown_cmark::{html, Event, HeadingLevel, Options, Parser, Tag};
+use serde_yaml::{Mapping, Value};
 
-pub fn parse_markdown_with_meta(input: &str) -> Result<(String, String)> {
-    let splits: Vec<&str> = input.split("---").collect();
-    if splits.len() != 3 {
-        return Err(anyhow!("Invalid meta section!"));
+/// Marks the end of the excerpt of Markdown content
+const MORE_MARKER: &str = "<!-- more -->";
+
+/// Format of the front matter of a content file, recognized by its first line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontMatterFormat {
+    /// Between `---` lines
+    Yaml,
+    /// Between `+++` lines
+    Toml,
+    /// A JSON object starting with `{`
+    Json,
+}
+
+impl FrontMatterFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            FrontMatterFormat::Yaml => "YAML",
+            FrontMatterFormat::Toml => "TOML",
+            FrontMatterFormat::Json => "JSON",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FrontMatter {
+    pub format: FrontMatterFormat,
+    pub source: String,
+    /// Line of the file where the source starts, starting at 1
+    pub line: usize,
+}
+
+impl FrontMatter {
+    /// Parses the front matter into a YAML mapping, which is empty for empty front matter
+    pub fn to_mapping(&self) -> Result<Mapping> {
+        let mapping: Result<Mapping> = match self.format {
+            FrontMatterFormat::Yaml => serde_yaml::from_str::<Option<Mapping>>(&self.source)
+                .map(Option::unwrap_or_default)
+                .map_err(Into::into),
+            FrontMatterFormat::Toml => toml::from_str::<toml::value::Table>(&self.source)
+                .map(|table| {
+                    table
+                        .into_iter()
+                        .map(|(key, value)| (Value::String(key), toml_to_yaml(value)))
+                        .collect()
+                })
+                .map_err(Into::into),
+            FrontMatterFormat::Json => serde_json::from_str(&self.source).map_err(Into::into),
+        };
+        mapping.with_context(|| format!("Failed to parse the {} front matter", self.format.name()))
+    }
+
+    /// Line and column in the file of an error of `to_mapping`
+    pub fn error_position(&self, error: &anyhow::Error) -> Option<(usize, usize)> {
+        error_position(error, self.line)
+    }
+
+    /// Line and column in the file of the value of a top level key, or of the key when the value
+    /// is on the next lines
+    pub fn key_position(&self, key: &str) -> Option<(usize, usize)> {
+        let quoted = [key.to_string(), format!("\"{key}\""), format!("'{key}'")];
+        for (index, line) in self.source.lines().enumerate() {
+            // Keys of JSON objects are indented, top level YAML and TOML keys are not
+            let key_line = match self.format {
+                FrontMatterFormat::Json => line.trim_start(),
+                _ => line,
+            };
+            let separator = match self.format {
+                FrontMatterFormat::Toml => '=',
+                _ => ':',
+            };
+            let value = quoted.iter().find_map(|quoted| {
+                key_line
+                    .strip_prefix(quoted.as_str())?
+                    .trim_start()
+                    .strip_prefix(separator)
+            });
+            if let Some(value) = value {
+                let value = value.trim_start();
+                let column = if value.is_empty() {
+                    line.len() - key_line.len()
+                } else {
+                    line.len() - value.len()
+                };
+                return Some((self.line + index, line[..column].chars().count() + 1));
+            }
+        }
+        None
+    }
+}
+
+/// Line and column of a YAML, TOML or JSON parse error in `error`, for a source that starts at
+/// line `first_line` of the file
+pub fn error_position(error: &anyhow::Error, first_line: usize) -> Option<(usize, usize)> {
+    let (line, column) = error.chain().find_map(|cause| {
+        if let Some(error) = cause.downcast_ref::<serde_yaml::Error>() {
+            let location = error.location()?;
+            Some((location.line(), location.column()))
+        } else if let Some(error) = cause.downcast_ref::<toml::de::Error>() {
+            let (line, column) = error.line_col()?;
+            Some((line + 1, column + 1))
+        } else {
+            let error = cause.downcast_ref::<serde_json::Error>()?;
+            Some((error.line(), error.column().max(1)))
+        }
+    })?;
+    Some((line + first_line - 1, column))
+}
+
+/// Converts TOML values to YAML, TOML dates become strings like the dates in YAML
+fn toml_to_yaml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(string) => Value::String(string),
+        toml::Value::Integer(integer) => Value::Number(integer.into()),
+        toml::Value::Float(float) => Value::Number(float.into()),
+        toml::Value::Boolean(boolean) => Value::Bool(boolean),
+        toml::Value::Datetime(date) => Value::String(date.to_string()),
+        toml::Value::Array(array) => Value::Sequence(array.into_iter().map(toml_to_yaml).collect()),
+        toml::Value::Table(table) => Value::Mapping(
+            table
+                .into_iter()
+                .map(|(key, value)| (Value::String(key), toml_to_yaml(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Splits the front matter from the Markdown source, files without front matter are all content
+///
+/// YAML and TOML front matter start and end with a delimiter on its own line, so a `---` in the
+/// content, like a horizontal rule, is not mistaken for the end of the front matter. JSON front
+/// matter ends with the object, content that starts with a `{` but not with a JSON object has
+/// no front matter.
+pub fn split_front_matter(input: &str) -> Result<(Option<FrontMatter>, &str)> {
+    let input = input.trim_start_matches('\u{feff}');
+    if input.trim_start().starts_with('{') {
+        let mut objects =
+            serde_json::Deserializer::from_str(input).into_iter::<serde_json::Value>();
+        if let Some(Ok(serde_json::Value::Object(_))) = objects.next() {
+            let end = objects.byte_offset();
+            let front_matter = FrontMatter {
+                format: FrontMatterFormat::Json,
+                source: input[..end].to_string(),
+                line: 1,
+            };
+            return Ok((Some(front_matter), skip_line_end(&input[end..])));
+        }
+        return Ok((None, input));
+    }
+
+    let (first_line, mut rest) = split_line(input);
+    let (format, delimiter) = match first_line.trim_end() {
+        "---" => (FrontMatterFormat::Yaml, "---"),
+        "+++" => (FrontMatterFormat::Toml, "+++"),
+        _ => return Ok((None, input)),
+    };
+    let source_start = input.len() - rest.len();
+    while !rest.is_empty() {
+        let line_start = input.len() - rest.len();
+        let (line, next) = split_line(rest);
+        if line.trim_end() == delimiter {
+            let front_matter = FrontMatter {
+                format,
+                source: input[source_start..line_start].to_string(),
+                line: 2,
+            };
+            return Ok((Some(front_matter), next));
+        }
+        rest = next;
+    }
+    bail!(
+        "The {} front matter is not closed by a '{delimiter}' line",
+        format.name()
+    )
+}
+
+/// Splits the first line without line ending from the rest of the input
+fn split_line(input: &str) -> (&str, &str) {
+    match input.find('\n') {
+        Some(index) => (&input[..index], &input[index + 1..]),
+        None => (input, ""),
+    }
+}
+
+/// Skips the rest of the line when it only contains whitespace
+fn skip_line_end(input: &str) -> &str {
+    let (line, rest) = split_line(input);
+    if line.trim().is_empty() {
+        rest
+    } else {
+        input
     }
-    Ok((splits[1].to_string(), markdown_to_html(&splits[2])))
 }
 
-fn markdown_to_html(input: &str) -> String {
+/// Text of the first level 1 heading of the Markdown source
+pub fn first_heading(markdown: &str) -> Option<String> {
+    let mut parser = Parser::new(markdown);
+    parser.find(|event| matches!(event, Event::Start(Tag::Heading(HeadingLevel::H1, _, _))))?;
+    let mut title = String::new();
+    for event in parser {
+        match event {
+            Event::Text(text) | Event::Code(text) => title.push_str(&text),
+            Event::SoftBreak | Event::HardBreak => title.push(' '),
+            Event::End(Tag::Heading(..)) => break,
+            _ => {}
+        }
+    }
+    Some(title.trim().to_string()).filter(|title| !title.is_empty())
+}
+
+/// HTML of the Markdown up to a `<!-- more -->` marker, if it has one
+///
+/// Only a marker between the top-level blocks ends the excerpt, a marker inside a paragraph or
+/// list would cut an element in half.
+pub fn markdown_excerpt(input: &str) -> Option<String> {
+    let mut depth = 0;
+    let mut events = Vec::new();
+    for event in Parser::new_ext(input, markdown_options()) {
+        match &event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth -= 1,
+            Event::Html(html) if depth == 0 && html.trim_start().starts_with(MORE_MARKER) => {
+                let mut excerpt = String::new();
+                html::push_html(&mut excerpt, events.into_iter());
+                return Some(excerpt.trim_end().to_string());
+            }
+            _ => {}
+        }
+        events.push(event);
+    }
+    None
+}
+
+/// The first paragraph of rendered HTML content
+pub fn first_paragraph(html: &str) -> &str {
+    match html.find("</p>") {
+        Some(index) => &html[..index + "</p>".len()],
+        None => html,
+    }
+}
+
+pub fn markdown_to_html(input: &str) -> String {
+    let parser = Parser::new_ext(input, markdown_options());
+
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, parser);
+
+    html_output
+}
+
+fn markdown_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_FOOTNOTES);
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_TASKLISTS);
-    let parser = Parser::new_ext(input, options);
+    options
+}
 
-    let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    html_output
+    /// Splits the input and returns the front matter as a mapping with the content
+    fn split(input: &str) -> (Option<(FrontMatterFormat, Mapping)>, &str) {
+        let (front_matter, content) = split_front_matter(input).unwrap();
+        let front_matter = front_matter
+            .map(|front_matter| (front_matter.format, front_matter.to_mapping().unwrap()));
+        (front_matter, content)
+    }
+
+    fn title(mapping: &Mapping) -> Option<&str> {
+        mapping.get(&Value::String("title".to_string()))?.as_str()
+    }
+
+    #[test]
+    fn horizontal_rule_in_content() {
+        let (front_matter, content) = split("---\ntitle: Rule\n---\nIntro\n\n---\n\nMore\n");
+        let (format, mapping) = front_matter.unwrap();
+        assert_eq!(format, FrontMatterFormat::Yaml);
+        assert_eq!(title(&mapping), Some("Rule"));
+        assert_eq!(content, "Intro\n\n---\n\nMore\n");
+    }
+
+    #[test]
+    fn delimiter_in_yaml_string() {
+        let input = "---\ntitle: a --- b\ndescription: |\n  ---\n  text\n---\nContent\n";
+        let (front_matter, content) = split(input);
+        let (_, mapping) = front_matter.unwrap();
+        assert_eq!(title(&mapping), Some("a --- b"));
+        let description = mapping.get(&Value::String("description".to_string()));
+        assert_eq!(description.and_then(Value::as_str), Some("---\ntext\n"));
+        assert_eq!(content, "Content\n");
+    }
+
+    #[test]
+    fn toml_front_matter() {
+        let (front_matter, content) =
+            split("+++\ntitle = \"Toml\"\ntags = [\"a\"]\n+++\nContent\n");
+        let (format, mapping) = front_matter.unwrap();
+        assert_eq!(format, FrontMatterFormat::Toml);
+        assert_eq!(title(&mapping), Some("Toml"));
+        assert_eq!(content, "Content\n");
+    }
+
+    #[test]
+    fn json_front_matter() {
+        let (front_matter, content) = split("{\n  \"title\": \"Json\"\n}\nContent\n");
+        let (format, mapping) = front_matter.unwrap();
+        assert_eq!(format, FrontMatterFormat::Json);
+        assert_eq!(title(&mapping), Some("Json"));
+        assert_eq!(content, "Content\n");
+    }
+
+    #[test]
+    fn content_starting_with_brace() {
+        let input = "{curly} is not JSON\n";
+        let (front_matter, content) = split(input);
+        assert!(front_matter.is_none());
+        assert_eq!(content, input);
+
+        let input = "{\"a\", \"list\"} of words\n";
+        let (front_matter, content) = split(input);
+        assert!(front_matter.is_none());
+        assert_eq!(content, input);
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let (front_matter, content) = split("\u{feff}---\ntitle: Bom\n---\nContent\n");
+        assert_eq!(title(&front_matter.unwrap().1), Some("Bom"));
+        assert_eq!(content, "Content\n");
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let (front_matter, content) = split("---\r\ntitle: Crlf\r\n---\r\nContent\r\n");
+        assert_eq!(title(&front_matter.unwrap().1), Some("Crlf"));
+        assert_eq!(content, "Content\r\n");
+
+        let (front_matter, content) = split("+++\r\ntitle = \"Crlf\"\r\n+++\r\nContent\r\n");
+        assert_eq!(title(&front_matter.unwrap().1), Some("Crlf"));
+        assert_eq!(content, "Content\r\n");
+    }
+
+    #[test]
+    fn unclosed_front_matter() {
+        let error = split_front_matter("---\ntitle: Open\nContent\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The YAML front matter is not closed by a '---' line"
+        );
+        assert!(split_front_matter("+++\ntitle = \"Open\"\n").is_err());
+    }
+
+    #[test]
+    fn no_front_matter() {
+        let (front_matter, content) = split("# Note\n\nText\n");
+        assert!(front_matter.is_none());
+        assert_eq!(content, "# Note\n\nText\n");
+    }
 }
@@ -1,13 +1,23 @@
 use anyhow::Result;
-use chrono::{NaiveDateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use quick_xml::{de, se::Serializer, DeError, Writer};
 use serde::{Deserialize, Serialize};
 
+pub const ITUNES_NAMESPACE: &str = "http://www.itunes.com/dtds/podcast-1.0.dtd";
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct RssFeed {
     pub version: String,
     #[serde(rename = "xmlns:atom")]
     pub atom_namespace: String,
+    #[serde(rename = "xmlns:content")]
+    pub content_namespace: String,
+    #[serde(rename = "xmlns:dc")]
+    pub dc_namespace: String,
+    /// Only used by podcast feeds
+    #[serde(rename = "xmlns:itunes")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub itunes_namespace: Option<String>,
     #[serde(rename = "channel")]
     pub channels: Vec<RssChannel>,
 }
@@ -17,6 +27,9 @@ impl RssFeed {
         Self {
             version: "2.0".to_string(),
             atom_namespace: "http://www.w3.org/2005/Atom".to_string(),
+            content_namespace: "http://purl.org/rss/1.0/modules/content/".to_string(),
+            dc_namespace: "http://purl.org/dc/elements/1.1/".to_string(),
+            itunes_namespace: None,
             channels: vec![channel],
         }
     }
@@ -34,9 +47,21 @@ pub struct RssChannel {
     pub description: String,
     #[serde(rename = "$unflatten=lastBuildDate")]
     #[serde(with = "rfc_2822_date")]
-    pub last_build_date: NaiveDateTime,
+    pub last_build_date: DateTime<FixedOffset>,
     #[serde(rename = "$unflatten=generator")]
     pub generator: String,
+    #[serde(rename = "itunes:image")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub itunes_image: Option<ItunesImage>,
+    #[serde(rename = "$unflatten=itunes:author")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub itunes_author: Option<String>,
+    #[serde(rename = "itunes:category")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub itunes_category: Option<ItunesCategory>,
+    #[serde(rename = "$unflatten=itunes:explicit")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub itunes_explicit: Option<bool>,
     #[serde(rename = "item")]
     pub items: Vec<RssItem>,
 }
@@ -48,8 +73,12 @@ impl RssChannel {
             link: link.clone(),
             atom_link: RssAtomLink::from_link(link),
             description,
-            last_build_date: Utc::now().naive_utc(),
+            last_build_date: Utc::now().into(),
             generator: "renatic".to_string(),
+            itunes_image: None,
+            itunes_author: None,
+            itunes_category: None,
+            itunes_explicit: None,
             items,
         }
     }
@@ -63,10 +92,36 @@ pub struct RssItem {
     pub link: String,
     #[serde(rename = "$unflatten=description")]
     pub description: String,
+    /// The full HTML content
+    #[serde(rename = "$unflatten=content:encoded")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Email address of the author
+    #[serde(rename = "$unflatten=author")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Name of the author
+    #[serde(rename = "$unflatten=dc:creator")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    #[serde(rename = "category")]
+    #[serde(default)]
+    pub categories: Vec<RssCategory>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enclosure: Option<RssEnclosure>,
     pub guid: RssGuid,
+    /// Duration of a podcast episode in seconds or as `HH:MM:SS`
+    #[serde(rename = "$unflatten=itunes:duration")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub itunes_duration: Option<String>,
+    #[serde(rename = "$unflatten=itunes:episode")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub itunes_episode: Option<u32>,
+    /// Left out for entries without a date
     #[serde(rename = "$unflatten=pubDate")]
-    #[serde(with = "rfc_2822_date")]
-    pub pub_date: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "rfc_2822_date::option")]
+    pub pub_date: Option<DateTime<FixedOffset>>,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -77,6 +132,32 @@ pub struct RssGuid {
     pub is_permalink: bool,
 }
 
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RssCategory {
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+/// Media file attached to an item, like the audio of a podcast episode
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RssEnclosure {
+    pub url: String,
+    /// Size in bytes
+    pub length: u64,
+    #[serde(rename = "type")]
+    pub mime_type: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ItunesImage {
+    pub href: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ItunesCategory {
+    pub text: String,
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct RssAtomLink {
     pub href: String,
@@ -96,24 +177,54 @@ impl RssAtomLink {
 }
 
 mod rfc_2822_date {
-    use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+    use chrono::{DateTime, FixedOffset};
     use serde::{self, Deserialize, Deserializer, Serializer};
 
-    pub fn serialize<S>(date: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    pub fn serialize<S>(date: &DateTime<FixedOffset>, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let date_time: DateTime<Utc> = Utc.from_local_datetime(&date).unwrap();
-        serializer.serialize_str(&date_time.to_rfc2822())
+        serializer.serialize_str(&date.to_rfc2822())
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<FixedOffset>, D::Error>
     where
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        let dt = DateTime::parse_from_rfc2822(&s).map_err(serde::de::Error::custom)?;
-        Ok(dt.naive_utc())
+        DateTime::parse_from_rfc2822(&s).map_err(serde::de::Error::custom)
+    }
+
+    pub mod option {
+        use chrono::{DateTime, FixedOffset};
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S>(
+            date: &Option<DateTime<FixedOffset>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match date {
+                Some(date) => super::serialize(date, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<FixedOffset>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(s) => DateTime::parse_from_rfc2822(&s)
+                    .map(Some)
+                    .map_err(serde::de::Error::custom),
+                None => Ok(None),
+            }
+        }
     }
 }
 
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use quick_xml::{se::Serializer, Writer};
+use serde::Serialize;
+
+/// Maximum number of URLs in one sitemap file, larger sitemaps are split up
+pub const MAX_URLS: usize = 50_000;
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+const SITEMAP_NAMESPACE: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+
+#[derive(Clone, Serialize)]
+pub struct UrlSet {
+    pub xmlns: String,
+    #[serde(rename = "url")]
+    pub urls: Vec<SitemapUrl>,
+}
+
+impl UrlSet {
+    pub fn new(urls: Vec<SitemapUrl>) -> Self {
+        Self {
+            xmlns: SITEMAP_NAMESPACE.to_string(),
+            urls,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct SitemapUrl {
+    #[serde(rename = "$unflatten=loc")]
+    pub loc: String,
+    /// Date of the last modification in the W3C datetime format
+    #[serde(rename = "$unflatten=lastmod")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastmod: Option<String>,
+}
+
+/// Sitemap listing other sitemaps
+#[derive(Clone, Serialize)]
+pub struct SitemapIndex {
+    pub xmlns: String,
+    #[serde(rename = "sitemap")]
+    pub sitemaps: Vec<SitemapRef>,
+}
+
+impl SitemapIndex {
+    pub fn new(sitemaps: Vec<SitemapRef>) -> Self {
+        Self {
+            xmlns: SITEMAP_NAMESPACE.to_string(),
+            sitemaps,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct SitemapRef {
+    #[serde(rename = "$unflatten=loc")]
+    pub loc: String,
+}
+
+pub fn to_str(url_set: UrlSet) -> Result<String> {
+    serialize(url_set, "urlset")
+}
+
+pub fn index_to_str(index: SitemapIndex) -> Result<String> {
+    serialize(index, "sitemapindex")
+}
+
+fn serialize(value: impl Serialize, root: &str) -> Result<String> {
+    let mut buffer = Vec::new();
+    let writer = Writer::new_with_indent(&mut buffer, b' ', 2);
+    let mut ser = Serializer::with_root(writer, Some(root));
+
+    value.serialize(&mut ser)?;
+    let string = String::from_utf8(buffer)?;
+    Ok(XML_DECLARATION.to_string() + &string)
+}
@@ -0,0 +1,61 @@
+/// Converts a name into a lowercase URL friendly slug, like `Rust & WebAssembly` into
+/// `rust-webassembly`
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Converts a slug like `my-first-post` into a title like `My First Post`
+pub fn title_from_slug(slug: &str) -> String {
+    slug.split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn punctuation_and_spaces() {
+        assert_eq!(slugify("Rust & WebAssembly"), "rust-webassembly");
+        assert_eq!(slugify("  Hello,  World!  "), "hello-world");
+        assert_eq!(slugify("static_site-generator"), "static-site-generator");
+        assert_eq!(slugify("2024 in review"), "2024-in-review");
+    }
+
+    #[test]
+    fn non_ascii_letters() {
+        assert_eq!(slugify("Café Crème"), "café-crème");
+        assert_eq!(slugify("ÜBER"), "über");
+        assert_eq!(slugify("日本語"), "日本語");
+    }
+
+    #[test]
+    fn same_slug_for_spellings() {
+        assert_eq!(slugify("Web Development"), slugify("web-development"));
+        assert_eq!(slugify("--"), "");
+    }
+
+    #[test]
+    fn title_of_slug() {
+        assert_eq!(title_from_slug("my-first_post"), "My First Post");
+        assert_eq!(title_from_slug("--a--b"), "A B");
+        assert_eq!(title_from_slug(""), "");
+    }
+}
@@ -0,0 +1,81 @@
+use crate::generator::Generator;
+use anyhow::{Context, Result};
+use log::{error, info, trace};
+use notify::{RecursiveMode, Watcher};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+/// Time to wait for more changes before rebuilding, editors often write multiple events per save
+const DEBOUNCE_TIME: Duration = Duration::from_millis(100);
+
+/// Generates the site and regenerates it every time a file in the source directory changes
+///
+/// Failed builds are logged and don't stop the watcher. The `on_rebuild` callback is called after
+/// every successful build.
+pub fn watch(mut generator: Generator, mut on_rebuild: impl FnMut()) -> Result<()> {
+    let source_dir = generator.source_dir().to_path_buf();
+    let out_dir = generator.out_dir().to_path_buf();
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to create file watcher")?;
+    watcher
+        .watch(&source_dir, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch '{}'", source_dir.display()))?;
+
+    let mut changed_paths = Vec::new();
+    loop {
+        let result = generator
+            .reload(&changed_paths)
+            .and_then(|_| generator.generate());
+        match result {
+            Ok(()) => on_rebuild(),
+            Err(e) => error!("{e:?}"),
+        }
+        info!("Watching '{}' for changes", source_dir.display());
+
+        // Wait for relevant changes and collect everything that changes shortly after
+        changed_paths.clear();
+        while changed_paths.is_empty() {
+            let event = rx.recv().context("File watcher stopped")?;
+            collect_paths(event, &mut changed_paths, &source_dir, &out_dir);
+        }
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE_TIME) {
+            collect_paths(event, &mut changed_paths, &source_dir, &out_dir);
+        }
+        for path in changed_paths.iter() {
+            trace!("Changed '{}'", path.display());
+        }
+    }
+}
+
+fn collect_paths(
+    event: notify::Result<notify::Event>,
+    changed_paths: &mut Vec<PathBuf>,
+    source_dir: &Path,
+    out_dir: &Path,
+) {
+    let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+            error!("File watcher error: {e}");
+            return;
+        }
+    };
+    if event.kind.is_access() {
+        return;
+    }
+    for path in event.paths {
+        // Ignore the generated output and hidden files like editor swap files or the .git directory
+        let is_hidden = path.strip_prefix(source_dir).map_or(true, |child_path| {
+            child_path
+                .components()
+                .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+        });
+        if path.starts_with(out_dir) || is_hidden || changed_paths.contains(&path) {
+            continue;
+        }
+        changed_paths.push(path);
+    }
+}